@@ -106,6 +106,114 @@ where
     }
 }
 
+/// Return the cells on a straight hex line from origin to the end vector.
+///
+/// Both endpoints are included and consecutive cells are always hex neighbors.
+pub fn hex_line(end: CellVector) -> Vec<CellVector> {
+    let n = end.hex_dist();
+    if n == 0 {
+        return vec![vec2(0, 0)];
+    }
+
+    // Nudge the line slightly off center so points exactly between two cells resolve
+    // consistently.
+    const NUDGE: f32 = 1e-3;
+    (0..=n)
+        .map(|i| {
+            let t = i as f32 / n as f32;
+            hex_round(t * end.x as f32 + NUDGE, t * end.y as f32 + NUDGE / 2.0)
+        })
+        .collect()
+}
+
+/// Return the cells touched by a straight hex line from origin to the end vector.
+///
+/// Unlike `hex_line`, when the line runs exactly between two cells both of them are included.
+pub fn hex_supercover(end: CellVector) -> Vec<CellVector> {
+    let n = end.hex_dist();
+    if n == 0 {
+        return vec![vec2(0, 0)];
+    }
+
+    const NUDGE: f32 = 1e-3;
+    let mut ret: Vec<CellVector> = Vec::new();
+    for i in 0..=n {
+        let t = i as f32 / n as f32;
+        let (x, y) = (t * end.x as f32, t * end.y as f32);
+        for &(dx, dy) in &[(NUDGE, NUDGE / 2.0), (-NUDGE, -NUDGE / 2.0)] {
+            let p = hex_round(x + dx, y + dy);
+            if !ret.contains(&p) {
+                ret.push(p);
+            }
+        }
+    }
+    ret
+}
+
+/// Return an iterator for the points at exactly the given distance from origin.
+pub fn hex_ring<P, R>(origin: P, radius: i32) -> impl Iterator<Item = R>
+where
+    P: Clone + Add<CellVector, Output = R>,
+{
+    let len = if radius == 0 { 1 } else { 6 * radius };
+    (0..len).map(move |i| {
+        if radius == 0 {
+            return origin.clone() + vec2(0, 0);
+        }
+        let rod = Dir6::from_int(i / radius);
+        let tangent = Dir6::from_int(i / radius + 2);
+        origin.clone() + (rod.to_v2() * radius + tangent.to_v2() * (i % radius))
+    })
+}
+
+/// Return the points within radius whose on-screen bearing is inside the wedge.
+///
+/// Angles are in radians, clockwise from north, see `Dir12::to_angle`. Width is the full angle
+/// of the wedge. The origin point is not included.
+pub fn hex_wedge(angle: f32, width: f32, radius: i32) -> Vec<CellVector> {
+    // Leeway for points that lie exactly on the wedge edges.
+    const EPSILON: f32 = 1e-3;
+
+    hex_disc(vec2(0, 0), radius)
+        .skip(1)
+        .filter(|&v: &CellVector| {
+            let mut diff = (bearing(v) - angle).rem_euclid(2.0 * PI);
+            if diff > PI {
+                diff -= 2.0 * PI;
+            }
+            diff.abs() <= width / 2.0 + EPSILON
+        })
+        .collect()
+}
+
+/// Return the points in a 60 degree cone pointing in the given direction.
+pub fn hex_cone(dir: impl Into<Dir12>, radius: i32) -> Vec<CellVector> {
+    hex_wedge(dir.into().to_angle(), PI / 3.0, radius)
+}
+
+/// Return the angle of a vector as it appears on screen in radians clockwise from north.
+fn bearing(v: CellVector) -> f32 {
+    let east = (v.x - v.y) as f32 * 3f32.sqrt() / 2.0;
+    let north = -(v.x + v.y) as f32 / 2.0;
+    east.atan2(north)
+}
+
+/// Round fractional cell coordinates to the closest hex cell.
+fn hex_round(x: f32, y: f32) -> CellVector {
+    // Cube coordinates of the point, a + b + c = 0.
+    let (a, b, c) = (x, -y, y - x);
+    let (mut ra, mut rb, rc) = (a.round(), b.round(), c.round());
+    let (da, db, dc) = ((ra - a).abs(), (rb - b).abs(), (rc - c).abs());
+
+    // Recompute the coordinate with the largest rounding error from the other two.
+    if da > db && da > dc {
+        ra = -rb - rc;
+    } else if db > dc {
+        rb = -ra - rc;
+    }
+    vec2(ra as i32, -rb as i32)
+}
+
 /// Hex grid directions.
 #[derive(Copy, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Dir6 {
@@ -175,6 +283,9 @@ impl Dir6 {
         RandomPermutation::new(rng, 6).map(|i| DIRS[i]).collect()
     }
 
+    /// Return the angle of the direction on screen in radians clockwise from north.
+    pub fn to_angle(self) -> f32 { self as i32 as f32 * PI / 3.0 }
+
     /// Return whether the direction is along the axes used in fake isometric graphics.
     pub fn is_fake_isometric(self) -> bool {
         use crate::Dir6::*;
@@ -236,6 +347,9 @@ pub enum Dir12 {
 }
 
 impl Dir12 {
+    /// Return the angle of the direction on screen in radians clockwise from north.
+    pub fn to_angle(self) -> f32 { self as i32 as f32 * PI / 6.0 }
+
     /// If there is exactly one cluster of neighbors in the neighbor mask,
     /// return a direction pointing away from that cluster.
     pub fn away_from(neighbors: [bool; 6]) -> Option<Dir12> {
@@ -300,6 +414,50 @@ impl Dir12 {
     }
 }
 
+impl From<Dir6> for Dir12 {
+    fn from(d: Dir6) -> Self {
+        // XXX: Same transmute trick as in away_from, Dir6 values map to the even Dir12 values.
+        unsafe { ::std::mem::transmute((d as u8) * 2) }
+    }
+}
+
+/// Rotation and reflection of hex vectors around the origin.
+#[derive(Copy, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct HexTransform {
+    /// Rotate clockwise by the angle this direction makes with north.
+    pub rotation: Dir6,
+    /// Mirror across the north-south axis before rotating.
+    pub mirror: bool,
+}
+
+impl Default for HexTransform {
+    fn default() -> Self { HexTransform::new(Dir6::North, false) }
+}
+
+impl HexTransform {
+    pub fn new(rotation: Dir6, mirror: bool) -> HexTransform { HexTransform { rotation, mirror } }
+
+    /// Iterate through all twelve distinct transforms, starting with identity.
+    pub fn iter() -> impl Iterator<Item = HexTransform> {
+        [false, true]
+            .iter()
+            .flat_map(|&mirror| Dir6::iter().map(move |&rot| HexTransform::new(rot, mirror)))
+    }
+
+    /// Return whether north and south stay where they are under the transform.
+    pub fn keeps_north(self) -> bool { self.rotation == Dir6::North }
+
+    pub fn apply(self, v: CellVector) -> CellVector {
+        let mut v = if self.mirror { vec2(v.y, v.x) } else { v };
+        for _ in 0..self.rotation as i32 {
+            v = vec2(v.x - v.y, v.x);
+        }
+        v
+    }
+
+    pub fn apply_dir(self, dir: Dir6) -> Dir6 { Dir6::from_v2(self.apply(dir.to_v2())) }
+}
+
 /// Staggered hex coordinate space
 ///
 ///    00    20
@@ -337,9 +495,12 @@ impl project::From<StaggeredHexSpace> for CellSpace {
 #[cfg(test)]
 mod test {
     use super::hex_disc;
+    use super::hex_line;
     use super::Dir12;
     use super::Dir6;
     use super::Dir6::*;
+    use super::HexTransform;
+    use super::{hex_cone, hex_ring, hex_supercover, hex_wedge};
     use euclid::vec2;
 
     #[test]
@@ -435,4 +596,120 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_hex_line() {
+        use super::HexGeom;
+
+        assert_eq!(vec![vec2(0, 0)], hex_line(vec2(0, 0)));
+        assert_eq!(
+            vec![vec2(0, 0), vec2(-1, -1), vec2(-2, -2)],
+            hex_line(vec2(-2, -2))
+        );
+
+        for end in hex_disc(vec2(0, 0), 8) {
+            let line = hex_line(end);
+            assert_eq!(line.len() as i32, end.hex_dist() + 1);
+            assert_eq!(line[0], vec2(0, 0));
+            assert_eq!(*line.last().unwrap(), end);
+            for w in line.windows(2) {
+                assert_eq!((w[1] - w[0]).hex_dist(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_supercover() {
+        use super::HexGeom;
+
+        // Straight lines are the same as regular lines.
+        assert_eq!(hex_line(vec2(3, 3)), hex_supercover(vec2(3, 3)));
+
+        // Line between two hex directions passes between cells.
+        let cover = hex_supercover(vec2(-1, -2));
+        assert_eq!(cover.len(), 4);
+        assert!(cover.contains(&vec2(-1, -1)));
+        assert!(cover.contains(&vec2(0, -1)));
+
+        for end in hex_disc(vec2(0, 0), 6) {
+            let cover = hex_supercover(end);
+            assert_eq!(*cover.last().unwrap(), end);
+            for w in cover.windows(2) {
+                assert_eq!((w[1] - w[0]).hex_dist(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_ring() {
+        use super::HexGeom;
+
+        assert_eq!(
+            vec![vec2(0, 0)],
+            hex_ring(vec2(0, 0), 0).collect::<Vec<_>>()
+        );
+        for r in 1..6 {
+            let ring: Vec<_> = hex_ring(vec2(0, 0), r).collect();
+            assert_eq!(ring.len() as i32, 6 * r);
+            assert!(ring.iter().all(|v| v.hex_dist() == r));
+        }
+    }
+
+    #[test]
+    fn test_hex_cone() {
+        use super::HexGeom;
+        use std::f32::consts::PI;
+
+        assert_eq!(vec![vec2(-1, -1)], hex_cone(North, 1));
+        let cone = hex_cone(North, 2);
+        assert_eq!(cone.len(), 4);
+        assert!(cone.contains(&vec2(-1, -2)));
+        assert!(cone.contains(&vec2(-2, -1)));
+
+        // Transitional directions point between two neighbors.
+        let cone = hex_cone(Dir12::NorthNortheast, 1);
+        assert_eq!(cone.len(), 2);
+        assert!(cone.contains(&vec2(-1, -1)));
+        assert!(cone.contains(&vec2(0, -1)));
+
+        // Full circle wedge is the whole disc without origin.
+        assert_eq!(hex_wedge(0.0, 2.0 * PI, 3).len(), 36);
+        for &d in Dir6::iter() {
+            assert!(hex_cone(d, 4).iter().all(|v| v.hex_dist() <= 4));
+            assert!(hex_cone(d, 4).contains(&(d.to_v2() * 4)));
+        }
+    }
+
+    #[test]
+    fn test_hex_transform() {
+        let v = vec2(2, -3);
+        assert_eq!(v, HexTransform::default().apply(v));
+        assert_eq!(12, HexTransform::iter().count());
+
+        // Transforms keep the grid intact.
+        for t in HexTransform::iter() {
+            for &d in Dir6::iter() {
+                assert_eq!(t.apply(d.to_v2()), t.apply_dir(d).to_v2());
+                assert_eq!(t.apply(v + d.to_v2()), t.apply(v) + t.apply_dir(d).to_v2());
+            }
+        }
+
+        assert_eq!(
+            Northeast,
+            HexTransform::new(Northeast, false).apply_dir(North)
+        );
+        assert_eq!(
+            South,
+            HexTransform::new(Southeast, false).apply_dir(Northeast)
+        );
+        assert_eq!(
+            Northwest,
+            HexTransform::new(North, true).apply_dir(Northeast)
+        );
+        assert_eq!(North, HexTransform::new(North, true).apply_dir(North));
+        assert_eq!(
+            Southeast,
+            HexTransform::new(South, true).apply_dir(Northeast)
+        );
+    }
 }
@@ -24,7 +24,8 @@ pub use alg_misc::{
 pub use cell::{CellSpace, CellVector, Fov, FovValue, PolarPoint};
 pub use colors::{term_color, BaseTermColor, PseudoTermColor, TermColor, Xterm256Color};
 pub use hex::{
-    hex_disc, hex_neighbors, taxicab_neighbors, Dir12, Dir6, HexDisc, HexGeom, StaggeredHexSpace,
+    hex_cone, hex_disc, hex_line, hex_neighbors, hex_ring, hex_supercover, hex_wedge,
+    taxicab_neighbors, Dir12, Dir6, HexDisc, HexGeom, HexTransform, StaggeredHexSpace,
 };
 pub use hex_fov::{AddFakeIsometricCorners, HexFov, HexFovIter, HexPolarPoint};
 pub use incremental::{History, Incremental, IncrementalState};
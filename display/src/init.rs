@@ -111,6 +111,9 @@ pub fn terrain_brushes() -> VecMap<Arc<Brush>> {
     ret.insert(Door as usize, Builder::new("assets/walls.png")
         .color(SADDLEBROWN).wall(128, 0, 160, 0)
         .color(LIGHTSLATEGRAY).wall(0, 0, 96, 0).finish());
+    ret.insert(LockedDoor as usize, Builder::new("assets/walls.png")
+        .color(GOLDENROD).wall(128, 0, 160, 0)
+        .color(LIGHTSLATEGRAY).wall(0, 0, 96, 0).finish());
     ret.insert(OpenDoor as usize, Builder::new("assets/walls.png").color(LIGHTSLATEGRAY).wall(0, 0, 96, 0).finish());
     ret.insert(Window as usize, Builder::new("assets/walls.png").color(LIGHTSLATEGRAY).wall(0, 0, 64, 0).finish());
     ret.insert(Pillar as usize, Builder::new("assets/props.png").color(GAINSBORO).tile(0, 32).finish());
@@ -172,6 +175,9 @@ pub fn entity_brushes() -> VecMap<Arc<Brush>> {
     ret.insert(Scroll1 as usize, Builder::new("assets/items.png").color(LIGHTYELLOW).item(0*16, 1*16).finish());
     ret.insert(Wand1 as usize, Builder::new("assets/items.png").color(RED).item(3*16, 0*16).finish());
     ret.insert(Wand2 as usize, Builder::new("assets/items.png").color(CYAN).item(3*16, 0*16).finish());
+    ret.insert(Key as usize, Builder::new("assets/items.png").color(GOLDENROD).item(3*16, 0*16).finish());
+    ret.insert(Crown as usize, Builder::new("assets/items.png").color(GOLD).item(1*16, 0*16).finish());
+    ret.insert(Corpse as usize, Builder::new("assets/fx.png").color(DARKRED).tile(4*32, 0).finish());
     ret
 }
 
@@ -22,9 +22,14 @@ use world::{
 /// Useful general constant for cell dimension ops.
 pub static PIXEL_UNIT: i32 = 16;
 
+/// Light level for remembered entities, dim enough to tell them apart from things in view.
+const REMEMBERED_ENTITY_LIGHT: f32 = 0.3;
+
 pub struct WorldView {
     pub cursor_loc: Option<Location>,
     pub show_cursor: bool,
+    /// Locations to mark on the map, such as the travel path under the mouse cursor.
+    pub highlight: Vec<Location>,
     camera_loc: LerpLocation,
     screen_area: ScreenRect,
     fov: Option<HashMap<CellVector, Vec<Location>>>,
@@ -35,6 +40,7 @@ impl WorldView {
         WorldView {
             cursor_loc: None,
             show_cursor: false,
+            highlight: Vec::new(),
             camera_loc,
             screen_area: ScreenRect::from_untyped(&screen_area),
             fov: None,
@@ -128,6 +134,13 @@ impl WorldView {
 
             let screen_pos = chart_pos.project() + center;
 
+            if self.highlight.contains(&loc) {
+                sprites.push(
+                    Sprite::new(Layer::Decal, screen_pos, cache::misc(Icon::CursorBottom))
+                        .color(Coloring::Solid(color::ORANGE)),
+                );
+            }
+
             let ambient = world.light_level(loc);
 
             let mut terrain_sprite_buffer = Vec::new();
@@ -184,25 +197,33 @@ impl WorldView {
                 }
             }
 
-            // Draw non-mob entities, these are static and shown in map memory
-            // FIXME: This should not use live entity data for the remembered objects, since it
-            // will then show the object moving around without the player observing it.
-            for &i in &items {
-                if let Some(desc) = world.ecs().desc.get(i) {
-                    let screen_pos = screen_pos + lerp_offset(world, i);
-                    let color = if in_map_memory {
-                        Coloring::MapMemory
-                    } else {
-                        Coloring::Shaded {
-                            ambient,
-                            diffuse: 1.0,
-                        }
+            // Draw non-mob entities. Remembered cells show the entities as they were last seen,
+            // not the live data.
+            if in_map_memory {
+                for remembered in world.remembered_entities(loc) {
+                    let color = Coloring::Shaded {
+                        ambient: REMEMBERED_ENTITY_LIGHT,
+                        diffuse: 1.0,
                     };
                     entity_sprite_buffer.push(
-                        Sprite::new(Layer::Object, screen_pos, cache::entity(desc.icon))
+                        Sprite::new(Layer::Object, screen_pos, cache::entity(remembered.icon))
                             .color(color),
                     );
                 }
+            } else {
+                for &i in &items {
+                    if let Some(desc) = world.ecs().desc.get(i) {
+                        let screen_pos = screen_pos + lerp_offset(world, i);
+                        let color = Coloring::Shaded {
+                            ambient,
+                            diffuse: 1.0,
+                        };
+                        entity_sprite_buffer.push(
+                            Sprite::new(Layer::Object, screen_pos, cache::entity(desc.icon))
+                                .color(color),
+                        );
+                    }
+                }
             }
 
             // Draw mobs in directly seen cells
@@ -371,7 +392,11 @@ impl WorldView {
         // Draw cursor.
         if let Some(origins) = chart.get(&cursor_pos) {
             let screen_pos = cursor_pos.project() + center;
-            let loc = origins[0] + cursor_pos;
+            // Match the location that is drawn, map memory comes from the base layer.
+            let mut loc = origins[0] + cursor_pos;
+            if get_fov(world, loc) != Some(FovStatus::Seen) {
+                loc = origins[origins.len() - 1] + cursor_pos;
+            }
             self.cursor_loc = Some(loc);
 
             if self.show_cursor {
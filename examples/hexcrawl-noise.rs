@@ -4,6 +4,7 @@ use world::Location;
 // Use smooth noise to wobblify the edges
 
 const HEX_SIZE: i32 = 20;
+const SEED: u32 = 1;
 //const NOISE: noise::OpenSimplex = noise::OpenSimplex::new();
 
 fn main() {
@@ -14,7 +15,7 @@ fn main() {
                 continue;
             }
             let (x, y) = ((c + r) / 2 - 50, r - 30);
-            let offset = Location::new(x as i16, y as i16, 0).terrain_cell_displacement();
+            let offset = Location::new(x as i16, y as i16, 0).terrain_cell_displacement(SEED);
 
             let r = calx::HexGeom::hex_dist(&vec2(x + offset.x, y + offset.y));
             if r < HEX_SIZE {
@@ -0,0 +1,200 @@
+//! Generate sector maps over many seeds, check them for broken invariants and print statistics.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    panic,
+};
+use structopt::StructOpt;
+use world::{Map, Sector, WorldSkeleton};
+
+#[derive(StructOpt, Debug)]
+struct Opt {
+    #[structopt(
+        long = "world",
+        default_value = "sprawl",
+        help = "Builtin world to generate"
+    )]
+    world: String,
+
+    #[structopt(
+        short = "n",
+        long = "seeds",
+        default_value = "1000",
+        help = "Number of seeds to generate"
+    )]
+    seeds: u32,
+
+    #[structopt(
+        long = "first-seed",
+        default_value = "0",
+        help = "First seed to generate"
+    )]
+    first_seed: u32,
+}
+
+#[derive(Default)]
+struct Stats {
+    maps: usize,
+    failures: usize,
+    open_area: Vec<usize>,
+    spawns: BTreeMap<String, usize>,
+    vaults: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    fn add(&mut self, map: &Map) {
+        self.maps += 1;
+        self.open_area
+            .push(map.iter().filter(|(_, c)| c.is_walkable()).count());
+        for (_, c) in map.iter() {
+            for spawn in &c.spawns {
+                *self.spawns.entry(spawn.to_string()).or_insert(0) += 1;
+            }
+        }
+        for name in map.placed_vaults() {
+            *self.vaults.entry(name.clone()).or_insert(0) += 1;
+        }
+    }
+
+    fn print(&self) {
+        println!("Generated {} maps, {} failures", self.maps, self.failures);
+
+        if !self.open_area.is_empty() {
+            let total: usize = self.open_area.iter().sum();
+            println!(
+                "Open area: min {}, mean {}, max {}",
+                self.open_area.iter().min().unwrap(),
+                total / self.open_area.len(),
+                self.open_area.iter().max().unwrap()
+            );
+        }
+
+        println!("Spawns per map:");
+        for (name, n) in &self.spawns {
+            println!("  {:<20} {:.2}", name, *n as f32 / self.maps as f32);
+        }
+
+        println!("Vault uses:");
+        for (name, n) in &self.vaults {
+            println!("  {:<20} {}", name, n);
+        }
+    }
+}
+
+/// Return the broken invariants of a generated sector map.
+fn check(sector: Sector, maps: &BTreeMap<Sector, Map>) -> Vec<String> {
+    let mut ret = Vec::new();
+    let map = &maps[&sector];
+
+    for (p, c) in map.iter() {
+        if !c.spawns.is_empty() && !c.is_walkable() {
+            ret.push(format!(
+                "Spawns {:?} on {:?} at {:?}",
+                c.spawns, c.terrain, p
+            ));
+        }
+    }
+
+    let regions = map.walkable_regions();
+    if sector.z < 0 && regions.len() > 1 {
+        ret.push(format!("{} disconnected regions", regions.len()));
+    }
+
+    // Overland maps can have islands, but stairs must be on the main landmass.
+    let main_region: HashSet<_> = regions
+        .iter()
+        .max_by_key(|r| r.len())
+        .map_or_else(HashSet::new, |r| r.iter().cloned().collect());
+    for p in map.upstairses().iter().chain(&map.downstairses()) {
+        if !main_region.contains(p) {
+            ret.push(format!("Unreachable stairs at {:?}", p));
+        }
+    }
+
+    // Other overland regions may connect through the neighboring sectors, but spawns on an
+    // island that doesn't reach the sector edge are stranded.
+    for p in map.stranded_positions() {
+        if !map[p].spawns.is_empty() {
+            ret.push(format!("Stranded spawns {:?} at {:?}", map[p].spawns, p));
+        }
+    }
+
+    let downstairs = map.downstairses().len();
+    match maps.get(&(sector + euclid::vec3(0, 0, -1))) {
+        None if downstairs > 0 => ret.push("Downstairs with no sector below".to_string()),
+        Some(below) if below.upstairses().len() != downstairs => ret.push(format!(
+            "{} downstairs but {} upstairs below",
+            downstairs,
+            below.upstairses().len()
+        )),
+        _ => {}
+    }
+    if downstairs > 1 {
+        ret.push(format!("{} downstairs", downstairs));
+    }
+    if !maps.contains_key(&(sector + euclid::vec3(0, 0, 1))) && !map.upstairses().is_empty() {
+        ret.push("Upstairs with no sector above".to_string());
+    }
+
+    if sector == Sector::new(0, 0, 0) {
+        if map.open_ground().is_empty() {
+            ret.push("No open ground for player entrance".to_string());
+        } else if !map
+            .get(map.player_entrance())
+            .map_or(false, |c| c.is_walkable())
+        {
+            ret.push(format!(
+                "Player entrance {:?} is not walkable",
+                map.player_entrance()
+            ));
+        }
+    }
+
+    ret
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    let skeleton = WorldSkeleton::builtin(&opt.world).unwrap_or_else(|| {
+        eprintln!("Unknown world {:?}", opt.world);
+        std::process::exit(1);
+    });
+
+    // Vaults with unknown glyphs will panic here, naming the bad vault.
+    let vaults = world::all_vaults();
+    println!("{} vaults ok", vaults.len());
+
+    let mut sectors: Vec<Sector> = skeleton.keys().cloned().collect();
+    sectors.sort();
+
+    let mut stats = Stats::default();
+    for seed in opt.first_seed..opt.first_seed + opt.seeds {
+        let mut maps = BTreeMap::new();
+        for &sector in &sectors {
+            // Keep going when generation crashes so the report shows every broken seed.
+            match panic::catch_unwind(|| world::generate_sector(seed, sector, &skeleton)) {
+                Ok(map) => {
+                    maps.insert(sector, map);
+                }
+                Err(_) => {
+                    stats.failures += 1;
+                    println!("seed {} sector {:?}: Generation panicked", seed, sector);
+                }
+            }
+        }
+
+        for (&sector, map) in &maps {
+            stats.add(map);
+            for failure in check(sector, &maps) {
+                stats.failures += 1;
+                println!("seed {} sector {:?}: {}", seed, sector, failure);
+            }
+        }
+    }
+
+    stats.print();
+    if stats.failures > 0 {
+        std::process::exit(1);
+    }
+}
@@ -1,15 +1,20 @@
 use crate::msg;
-use calx::{stego, CellVector, Dir6, IncrementalState};
+use calx::{stego, CellVector, Dir6, HexGeom, IncrementalState};
 use calx_ecs::Entity;
-use display::{CanvasExt, ScreenVector};
+use display::CanvasExt;
 use euclid::default::{Point2D, Rect};
 use euclid::{point2, size2, vec2};
+use log::info;
+use rand::Rng;
 use std::io::prelude::*;
 use std::io::Cursor;
 use vitral::{
     color, Align, ButtonAction, Canvas, InputEvent, Keycode, RectUtil, Rgba, Scene, SceneSwitch,
 };
-use world::{Ability, ActionOutcome, Command, LerpLocation, Slot, World, WorldSeed};
+use world::{
+    Ability, ActionOutcome, Background, Command, ExternalEntity, LerpLocation, Location, Slot,
+    Terrain, World, WorldSeed,
+};
 
 pub struct HotbarAction {
     ability: Ability,
@@ -17,12 +22,28 @@ pub struct HotbarAction {
     item: Option<Entity>,
 }
 
+/// Multi-turn player activity that keeps issuing commands until done or interrupted.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Autopilot {
+    Explore,
+    /// Walk to a known location.
+    Travel(Location),
+    /// Keep moving in a direction, following corridors.
+    Run(Dir6),
+    /// Pick up everything from the floor, one item per turn.
+    ///
+    /// Holds the size of the pile before the last pickup to stop if the pickup failed.
+    TakeAll(usize),
+}
+
 pub(crate) struct GameRuntime {
     world: IncrementalState<World>,
     command: Option<Command>,
     cursor_item: Option<Entity>,
     hotbar: [Option<HotbarAction>; 10],
     hotbar_focus: Option<usize>,
+    /// Running autopilot and the player's health when it last moved.
+    autopilot: Option<(Autopilot, i32)>,
 }
 
 impl GameRuntime {
@@ -33,6 +54,117 @@ impl GameRuntime {
             cursor_item: None,
             hotbar: Default::default(),
             hotbar_focus: None,
+            autopilot: None,
+        }
+    }
+
+    /// Throw away the current game and start a new one in the same world.
+    ///
+    /// The world gets a new seed unless the current game hasn't been played yet, so a seed given
+    /// on the command line survives character creation.
+    pub fn new_game(&mut self, player_character: ExternalEntity) {
+        let history = self.world.history();
+        let rng_seed = if history.events.is_empty() {
+            history.seed.rng_seed
+        } else {
+            rand::thread_rng().gen()
+        };
+        let seed = WorldSeed {
+            rng_seed,
+            world_skeleton: history.seed.world_skeleton.clone(),
+            player_character,
+        };
+        info!("World seed: {}", seed.rng_seed);
+        *self = GameRuntime::new(seed);
+
+        if let Some(player) = self.world.player() {
+            for ability in self.world.list_abilities(player) {
+                self.bind_ability(ability);
+            }
+        }
+    }
+
+    fn start_autopilot(&mut self, autopilot: Autopilot) {
+        if let Some(player) = self.world.player() {
+            self.autopilot = Some((autopilot, self.world.hp(player)));
+        }
+    }
+
+    /// Get the next command from the running autopilot.
+    ///
+    /// The autopilot is stopped when it runs out of things to do, when the player gets hurt or
+    /// when enemies show up.
+    fn autopilot_command(&mut self) -> Option<Command> {
+        let (autopilot, hp) = self.autopilot?;
+        self.autopilot = None;
+
+        let player = self.world.player()?;
+        let current_hp = self.world.hp(player);
+        if current_hp < hp {
+            return None;
+        }
+
+        match autopilot {
+            Autopilot::Explore => {
+                if !self.world.can_command(&Command::AutoExplore) {
+                    return None;
+                }
+                // Exploring with enemies in view makes the world point out the enemy, send the
+                // command but don't keep going.
+                if self.world.visible_hostiles(player).is_empty() {
+                    self.autopilot = Some((autopilot, current_hp));
+                }
+                Some(Command::AutoExplore)
+            }
+            Autopilot::Travel(destination) => {
+                if !self.world.visible_hostiles(player).is_empty() {
+                    return None;
+                }
+                let path = self.world.travel_path(player, destination)?;
+                // Stop after the final step.
+                if path.len() > 1 {
+                    self.autopilot = Some((autopilot, current_hp));
+                }
+                Some(Command::Step(path[0].0))
+            }
+            Autopilot::Run(dir) => {
+                let dir = self.world.run_dir(player, dir)?;
+                self.autopilot = Some((Autopilot::Run(dir), current_hp));
+                Some(Command::Step(dir))
+            }
+            Autopilot::TakeAll(prev_len) => {
+                let items = self.world.items_at(self.world.location(player)?);
+                if items.len() >= prev_len {
+                    return None;
+                }
+                if items.len() > 1 {
+                    self.autopilot = Some((Autopilot::TakeAll(items.len()), current_hp));
+                }
+                Some(Command::Take(*items.first()?))
+            }
+        }
+    }
+
+    /// Pick up an item from under the player, or show the whole pile if there are several.
+    fn take(&mut self) -> Option<SceneSwitch<GameRuntime>> {
+        let player = self.world.player()?;
+        let items = self.world.items_at(self.world.location(player)?);
+        match items.len() {
+            0 => None,
+            1 => {
+                self.command = Some(Command::Take(items[0]));
+                None
+            }
+            _ => Some(SceneSwitch::Push(Box::new(PileScreen::default()))),
+        }
+    }
+
+    /// Travel to the closest known location with the given terrain.
+    fn travel_to_terrain(&mut self, terrain: Terrain) {
+        if let Some(player) = self.world.player() {
+            if let Some(loc) = self.world.closest_known_terrain(player, terrain) {
+                self.start_autopilot(Autopilot::Travel(loc));
+            }
         }
     }
 
@@ -138,6 +270,16 @@ impl GameRuntime {
         }
     }
 
+    /// Put an innate ability in the first free hotbar slot.
+    fn bind_ability(&mut self, ability: Ability) {
+        if let Some(slot) = self.hotbar.iter_mut().find(|a| a.is_none()) {
+            *slot = Some(HotbarAction {
+                ability,
+                item: None,
+            });
+        }
+    }
+
     /// Handle missing items and invalidated abilities
     fn update_hotbar(&mut self) {
         for i in 0..self.hotbar.len() {
@@ -157,6 +299,10 @@ impl GameRuntime {
 pub struct GameLoop {
     pub console: display::Console,
     camera_loc: LerpLocation,
+    /// Shift key is held down, direction keys make the player run.
+    shift_held: bool,
+    /// Path the player would travel to reach the mouse cursor.
+    hover_path: Vec<Location>,
 }
 
 enum Side {
@@ -169,7 +315,22 @@ impl Scene<GameRuntime> for GameLoop {
         ctx.update_hotbar();
         self.process_events(ctx);
 
+        if ctx.world.game_over() {
+            ctx.autopilot = None;
+            ctx.command = None;
+            return Some(SceneSwitch::Push(Box::new(GameOverScreen)));
+        }
+
+        if ctx.world.player_can_act() && ctx.world.pending_perks() > 0 {
+            ctx.autopilot = None;
+            return Some(SceneSwitch::Push(Box::new(LevelUpScreen::default())));
+        }
+
         if ctx.world.player_can_act() {
+            if ctx.command.is_none() {
+                ctx.command = ctx.autopilot_command();
+            }
+
             if let Some(cmd) = ctx.command {
                 ctx.world.update(cmd);
                 ctx.command = None;
@@ -223,6 +384,7 @@ impl Scene<GameRuntime> for GameLoop {
 
         let mut view = display::WorldView::new(self.camera_loc, view_area);
         view.show_cursor = true;
+        view.highlight = self.hover_path.clone();
 
         canvas.set_clip(view_area);
         view.draw(&*ctx.world, canvas);
@@ -236,39 +398,64 @@ impl Scene<GameRuntime> for GameLoop {
         console_area.size.height = 32;
         self.console.draw_small(canvas, &console_area);
 
+        let mut switch = None;
+        self.hover_path.clear();
         if view_area.contains(canvas.mouse_pos()) {
-            let mouse_loc =
-                view.screen_to_cell(ScreenVector::from_untyped(canvas.mouse_pos().to_vector()));
+            // Use the cursor location from the drawn view, it knows about portals.
             (|| {
+                let mouse_loc = view.cursor_loc?;
                 let player = ctx.world.player()?;
-                let relative_vec = ctx.world.location(player)?.v2_at(mouse_loc)?;
+                let player_loc = ctx.world.location(player)?;
+                let relative_vec = ctx.world.chart_v2(player, player_loc, mouse_loc)?;
                 let click_state = canvas.click_state(&view_area);
 
+                // Copy the action out so ctx stays free for mutation below.
+                let focused_action = ctx
+                    .hotbar_focus
+                    .and_then(|i| ctx.hotbar[i].as_ref())
+                    .map(|a| (a.ability, a.item));
+                self.hover_path = if let Some((ability, _)) = focused_action {
+                    // Preview the area the focused ability would hit.
+                    ctx.world.ability_area(player, ability, mouse_loc)
+                } else {
+                    ctx.world
+                        .travel_path(player, mouse_loc)
+                        .map(|path| path.into_iter().map(|(_, loc)| loc).collect())
+                        .unwrap_or_default()
+                };
+
                 if click_state == ButtonAction::LeftClicked {
+                    ctx.autopilot = None;
                     if relative_vec == CellVector::zero() {
-                        ctx.command = Some(Command::Take);
-                    } else {
+                        switch = ctx.take();
+                    } else if relative_vec.hex_dist() == 1 {
+                        // Adjacent cells get stepped into or attacked.
                         let dir = Dir6::from_v2(relative_vec);
                         self.smart_step(ctx, dir);
+                    } else if focused_action.is_none() && !self.hover_path.is_empty() {
+                        ctx.start_autopilot(Autopilot::Travel(mouse_loc));
                     }
                 }
 
-                // Use targeted ability with RMB
                 if click_state == ButtonAction::RightClicked {
-                    if let Some(i) = ctx.hotbar_focus {
-                        if let Some(HotbarAction { ability, item }) = ctx.hotbar[i] {
-                            if relative_vec != CellVector::zero() {
-                                let dir = Dir6::from_v2(relative_vec);
-                                ctx.command = Some(Command::TargetedAbility { ability, dir, item });
-                            }
+                    // Use targeted ability with RMB if one is selected, otherwise inspect.
+                    if let Some((ability, item)) = focused_action {
+                        if relative_vec != CellVector::zero() {
+                            ctx.command = Some(Command::LocationTargetedAbility {
+                                ability,
+                                target: mouse_loc,
+                                item,
+                            });
                         }
+                    } else {
+                        self.inspect(ctx, mouse_loc);
                     }
                 }
                 Some(())
             })();
         }
 
-        None
+        switch
     }
 
     fn input(
@@ -277,6 +464,21 @@ impl Scene<GameRuntime> for GameLoop {
         event: &InputEvent,
         canvas: &mut Canvas,
     ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down,
+            hardware_key: Some(Keycode::LeftShift),
+            ..
+        }
+        | InputEvent::KeyEvent {
+            is_down,
+            hardware_key: Some(Keycode::RightShift),
+            ..
+        } = event
+        {
+            self.shift_held = *is_down;
+            return None;
+        }
+
         if let InputEvent::KeyEvent {
             is_down: true,
             hardware_key: Some(scancode),
@@ -285,24 +487,27 @@ impl Scene<GameRuntime> for GameLoop {
         {
             use Keycode::*;
 
+            // Any keypress interrupts the autopilot.
+            ctx.autopilot = None;
+
             match scancode {
                 Q | Pad7 | Home => {
-                    self.smart_step(ctx, Dir6::Northwest);
+                    self.walk(ctx, Dir6::Northwest);
                 }
                 W | Up | Pad8 => {
-                    self.smart_step(ctx, Dir6::North);
+                    self.walk(ctx, Dir6::North);
                 }
                 E | Pad9 | PageUp => {
-                    self.smart_step(ctx, Dir6::Northeast);
+                    self.walk(ctx, Dir6::Northeast);
                 }
                 A | Pad1 | End => {
-                    self.smart_step(ctx, Dir6::Southwest);
+                    self.walk(ctx, Dir6::Southwest);
                 }
                 S | Down | Pad2 => {
-                    self.smart_step(ctx, Dir6::South);
+                    self.walk(ctx, Dir6::South);
                 }
                 D | Pad3 | PageDown => {
-                    self.smart_step(ctx, Dir6::Southeast);
+                    self.walk(ctx, Dir6::Southeast);
                 }
                 Left | Pad4 => {
                     self.side_step(ctx, Side::West);
@@ -332,7 +537,41 @@ impl Scene<GameRuntime> for GameLoop {
                 }
 
                 G => {
-                    ctx.command = Some(Command::Take);
+                    return ctx.take();
+                }
+
+                X => {
+                    ctx.start_autopilot(Autopilot::Explore);
+                }
+                Period => {
+                    ctx.travel_to_terrain(Terrain::Downstairs);
+                }
+                Comma => {
+                    ctx.travel_to_terrain(Terrain::Upstairs);
+                }
+
+                L => {
+                    let cursor = ctx
+                        .world
+                        .player()
+                        .and_then(|p| ctx.world.location(p))
+                        .unwrap_or(self.camera_loc.location);
+                    return Some(SceneSwitch::Push(Box::new(LookMode::new(
+                        self.camera_loc,
+                        cursor,
+                    ))));
+                }
+
+                F => {
+                    let focused_action = ctx.hotbar_focus.and_then(|i| ctx.hotbar[i].as_ref());
+                    if let Some(&HotbarAction { ability, item }) = focused_action {
+                        let mode = TargetingMode::new(ctx, self.camera_loc, ability, item)?;
+                        return Some(SceneSwitch::Push(Box::new(mode)));
+                    }
+                }
+
+                C => {
+                    return Some(SceneSwitch::Push(Box::new(CharacterSheet)));
                 }
 
                 Escape => {
@@ -378,6 +617,17 @@ impl Scene<GameRuntime> for GameLoop {
 }
 
 impl GameLoop {
+    /// Step in a direction, or start running if shift is held down.
+    fn walk(&self, ctx: &mut GameRuntime, dir: Dir6) {
+        self.smart_step(ctx, dir);
+        if self.shift_held {
+            // Wall sliding may have changed the direction.
+            if let Some(Command::Step(dir)) = ctx.command {
+                ctx.start_autopilot(Autopilot::Run(dir));
+            }
+        }
+    }
+
     /// Step command that turns into melee attack if an enemy is in the way.
     fn smart_step(&self, ctx: &mut GameRuntime, dir: Dir6) -> ActionOutcome {
         let player = ctx.world.player()?;
@@ -441,15 +691,24 @@ impl GameLoop {
         self.smart_step(ctx, actual_dir)
     }
 
+    /// Describe what's at a location in the message console.
+    fn inspect(&mut self, ctx: &GameRuntime, loc: Location) {
+        for line in ctx.world.describe_location(loc) {
+            let _ = writeln!(&mut self.console, "{}", line);
+        }
+    }
+
     fn status_draw(&self, ctx: &mut GameRuntime, canvas: &mut Canvas, area: &Rect<i32>) {
         canvas.fill_rect(area, Rgba::from(0x33_11_11_ff));
-        canvas.draw_text(
-            &*display::font(),
-            area.origin,
-            Align::Left,
-            color::RED,
-            "Welcome to status bar",
-        );
+        if let Some(player) = ctx.world.player() {
+            canvas.draw_text(
+                &*display::font(),
+                area.origin,
+                Align::Left,
+                color::RED,
+                &ctx.world.entity_name(player),
+            );
+        }
 
         ctx.draw_hotbar(canvas);
     }
@@ -461,6 +720,718 @@ impl GameLoop {
     }
 }
 
+/// Cursor mode for examining things on the map.
+struct LookMode {
+    camera_loc: LerpLocation,
+    cursor: Location,
+    /// Mouse position on the previous frame, the cursor follows the mouse when it moves.
+    mouse_pos: Option<Point2D<i32>>,
+}
+
+impl LookMode {
+    fn new(camera_loc: LerpLocation, cursor: Location) -> LookMode {
+        LookMode {
+            camera_loc,
+            cursor,
+            mouse_pos: None,
+        }
+    }
+}
+
+impl Scene<GameRuntime> for LookMode {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let screen_area = canvas.screen_bounds();
+        let (view_area, text_area) = screen_area.horizontal_split(-48);
+
+        let mut view = display::WorldView::new(self.camera_loc, view_area);
+        view.highlight = vec![self.cursor];
+
+        canvas.set_clip(view_area);
+        view.draw(&*ctx.world, canvas);
+        canvas.clear_clip();
+
+        let mouse_pos = canvas.mouse_pos();
+        if self.mouse_pos.map_or(false, |p| p != mouse_pos) && view_area.contains(mouse_pos) {
+            if let Some(loc) = view.cursor_loc {
+                self.cursor = loc;
+            }
+        }
+        self.mouse_pos = Some(mouse_pos);
+
+        canvas.fill_rect(&text_area, Rgba::from(0x11_11_33_ff));
+        let mut pos = text_area.origin;
+        for line in ctx.world.describe_location(self.cursor) {
+            pos = canvas.draw_text(&*display::font(), pos, Align::Left, color::SILVER, &line);
+        }
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+
+            let dir = match scancode {
+                Q | Pad7 | Home => Dir6::Northwest,
+                W | Up | Pad8 => Dir6::North,
+                E | Pad9 | PageUp => Dir6::Northeast,
+                A | Pad1 | End => Dir6::Southwest,
+                S | Down | Pad2 => Dir6::South,
+                D | Pad3 | PageDown => Dir6::Southeast,
+                Escape | L => return Some(SceneSwitch::Pop),
+                _ => return None,
+            };
+            self.cursor = self.cursor.jump(&*ctx.world, dir);
+        }
+        None
+    }
+}
+
+/// Cursor mode for aiming a targeted ability.
+struct TargetingMode {
+    camera_loc: LerpLocation,
+    ability: Ability,
+    item: Option<Entity>,
+    cursor: Location,
+    /// Number of times the cursor has been cycled between visible hostiles.
+    target_idx: usize,
+    /// Mouse position on the previous frame, the cursor follows the mouse when it moves.
+    mouse_pos: Option<Point2D<i32>>,
+}
+
+impl TargetingMode {
+    /// Start aiming, with the cursor on the closest visible hostile if there is one.
+    fn new(
+        ctx: &GameRuntime,
+        camera_loc: LerpLocation,
+        ability: Ability,
+        item: Option<Entity>,
+    ) -> Option<TargetingMode> {
+        let player = ctx.world.player()?;
+        let cursor = ctx.world.location(player)?;
+        let mut ret = TargetingMode {
+            camera_loc,
+            ability,
+            item,
+            cursor,
+            target_idx: 0,
+            mouse_pos: None,
+        };
+        ret.snap_to_target(ctx);
+        Some(ret)
+    }
+
+    /// Move the cursor to the current hostile in the target cycle.
+    fn snap_to_target(&mut self, ctx: &GameRuntime) {
+        let hostiles = match ctx.world.player() {
+            Some(player) => ctx.world.visible_hostiles(player),
+            None => return,
+        };
+        if hostiles.is_empty() {
+            return;
+        }
+        if let Some(loc) = ctx
+            .world
+            .location(hostiles[self.target_idx % hostiles.len()])
+        {
+            self.cursor = loc;
+        }
+    }
+
+    fn fire(&self, ctx: &mut GameRuntime) -> Option<SceneSwitch<GameRuntime>> {
+        let cmd = Command::LocationTargetedAbility {
+            ability: self.ability,
+            target: self.cursor,
+            item: self.item,
+        };
+        // Stay in targeting mode if the target is invalid, eg. the player's own cell.
+        if !ctx.world.can_command(&cmd) {
+            return None;
+        }
+        ctx.command = Some(cmd);
+        Some(SceneSwitch::Pop)
+    }
+}
+
+impl Scene<GameRuntime> for TargetingMode {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let screen_area = canvas.screen_bounds();
+        let (view_area, text_area) = screen_area.horizontal_split(-48);
+
+        let mut view = display::WorldView::new(self.camera_loc, view_area);
+        if let Some(player) = ctx.world.player() {
+            view.highlight = ctx.world.ability_area(player, self.ability, self.cursor);
+        }
+        if !view.highlight.contains(&self.cursor) {
+            view.highlight.push(self.cursor);
+        }
+
+        canvas.set_clip(view_area);
+        view.draw(&*ctx.world, canvas);
+        canvas.clear_clip();
+
+        let mouse_pos = canvas.mouse_pos();
+        if view_area.contains(mouse_pos) {
+            if self.mouse_pos.map_or(false, |p| p != mouse_pos) {
+                if let Some(loc) = view.cursor_loc {
+                    self.cursor = loc;
+                }
+            }
+            if canvas.click_state(&view_area) == ButtonAction::LeftClicked {
+                return self.fire(ctx);
+            }
+        }
+        self.mouse_pos = Some(mouse_pos);
+
+        canvas.fill_rect(&text_area, Rgba::from(0x33_11_11_ff));
+        let mut pos = canvas.draw_text(
+            &*display::font(),
+            text_area.origin,
+            Align::Left,
+            color::ORANGE,
+            &format!(
+                "Aiming {:?}. Tab: next target, F: fire, Esc: cancel",
+                self.ability
+            ),
+        );
+        for line in ctx.world.describe_location(self.cursor) {
+            pos = canvas.draw_text(&*display::font(), pos, Align::Left, color::SILVER, &line);
+        }
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+
+            let dir = match scancode {
+                Q | Pad7 | Home => Dir6::Northwest,
+                W | Up | Pad8 => Dir6::North,
+                E | Pad9 | PageUp => Dir6::Northeast,
+                A | Pad1 | End => Dir6::Southwest,
+                S | Down | Pad2 => Dir6::South,
+                D | Pad3 | PageDown => Dir6::Southeast,
+                Tab => {
+                    self.target_idx += 1;
+                    self.snap_to_target(ctx);
+                    return None;
+                }
+                F | Enter | PadEnter => return self.fire(ctx),
+                Escape => return Some(SceneSwitch::Pop),
+                _ => return None,
+            };
+            self.cursor = self.cursor.jump(&*ctx.world, dir);
+        }
+        None
+    }
+}
+
+/// Name and background selection for a new player character.
+#[derive(Default)]
+pub struct CharacterCreation {
+    name: String,
+    /// Index of the selected background in `Background::iter`.
+    background: usize,
+}
+
+impl CharacterCreation {
+    const MAX_NAME_LENGTH: usize = 20;
+
+    fn background(&self) -> Background { *Background::iter().nth(self.background).unwrap() }
+}
+
+impl Scene<GameRuntime> for CharacterCreation {
+    fn render(
+        &mut self,
+        _ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let area = canvas.screen_bounds();
+        canvas.fill_rect(&area, color::BLACK);
+
+        let font = display::font();
+        let mut pos = canvas.draw_text(
+            &*font,
+            area.origin + vec2(8, 8),
+            Align::Left,
+            color::ORANGE,
+            "Create your character",
+        );
+        pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, "");
+        pos = canvas.draw_text(
+            &*font,
+            pos,
+            Align::Left,
+            color::SILVER,
+            &format!("Name: {}_", self.name),
+        );
+        pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, "");
+
+        for (i, background) in Background::iter().enumerate() {
+            let (marker, text_color) = if i == self.background {
+                ('>', color::YELLOW)
+            } else {
+                (' ', color::SILVER)
+            };
+            pos = canvas.draw_text(
+                &*font,
+                pos,
+                Align::Left,
+                text_color,
+                &format!(
+                    "{} {:<8} {}",
+                    marker,
+                    background.name(),
+                    background.description()
+                ),
+            );
+        }
+
+        pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, "");
+        canvas.draw_text(
+            &*font,
+            pos,
+            Align::Left,
+            color::SILVER,
+            "Type a name, Up/Down: choose background, Enter: start",
+        );
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        match event {
+            InputEvent::Typed(c) => {
+                // Keep names to plain words, '|' would be parsed as a plural form by `Desc`.
+                let valid = c.is_alphanumeric() || (*c == ' ' && !self.name.is_empty());
+                if valid && self.name.chars().count() < Self::MAX_NAME_LENGTH {
+                    self.name.push(*c);
+                }
+            }
+            InputEvent::KeyEvent {
+                is_down: true,
+                hardware_key: Some(scancode),
+                ..
+            } => {
+                use Keycode::*;
+                let n = Background::iter().len();
+                match scancode {
+                    Backspace => {
+                        self.name.pop();
+                    }
+                    Up => self.background = (self.background + n - 1) % n,
+                    Down => self.background = (self.background + 1) % n,
+                    Enter | PadEnter => {
+                        let name = match self.name.trim() {
+                            "" => "Adventurer",
+                            name => name,
+                        };
+                        ctx.new_game(self.background().player_character(name));
+                        return Some(SceneSwitch::Pop);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Modal perk selection when the player has gained a level.
+#[derive(Default)]
+struct LevelUpScreen {
+    selected: usize,
+}
+
+impl LevelUpScreen {
+    fn choose(&self, ctx: &mut GameRuntime) -> Option<SceneSwitch<GameRuntime>> {
+        let perk = *ctx.world.available_perks().get(self.selected)?;
+        if ctx.force_command(Command::ChoosePerk(perk)) {
+            if let Some(ability) = perk.ability() {
+                ctx.bind_ability(ability);
+            }
+        }
+        Some(SceneSwitch::Pop)
+    }
+}
+
+impl Scene<GameRuntime> for LevelUpScreen {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let area = canvas.screen_bounds();
+        canvas.fill_rect(&area, color::BLACK);
+
+        let font = display::font();
+        let mut pos = canvas.draw_text(
+            &*font,
+            area.origin + vec2(8, 8),
+            Align::Left,
+            color::ORANGE,
+            "You have gained a level! Choose a perk:",
+        );
+        pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, "");
+
+        for (i, perk) in ctx.world.available_perks().into_iter().enumerate() {
+            let text_color = if i == self.selected {
+                color::YELLOW
+            } else {
+                color::SILVER
+            };
+            pos = canvas.draw_text(
+                &*font,
+                pos,
+                Align::Left,
+                text_color,
+                &format!("{}) {:<12} {}", i + 1, perk.name(), perk.description()),
+            );
+        }
+
+        pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, "");
+        canvas.draw_text(
+            &*font,
+            pos,
+            Align::Left,
+            color::SILVER,
+            "Up/Down or number: choose perk, Enter: confirm",
+        );
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+            let n = ctx.world.available_perks().len();
+            if n == 0 {
+                return Some(SceneSwitch::Pop);
+            }
+
+            match scancode {
+                Up => self.selected = (self.selected + n - 1) % n,
+                Down => self.selected = (self.selected + 1) % n,
+                Enter | PadEnter => return self.choose(ctx),
+                Num1 | Num2 | Num3 | Num4 | Num5 | Num6 | Num7 | Num8 | Num9 => {
+                    let idx = *scancode as usize - Num1 as usize;
+                    if idx < n {
+                        self.selected = idx;
+                        return self.choose(ctx);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Player character's stats and chosen perks.
+struct CharacterSheet;
+
+impl Scene<GameRuntime> for CharacterSheet {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let area = canvas.screen_bounds();
+        canvas.fill_rect(&area, color::BLACK);
+
+        let player = match ctx.world.player() {
+            Some(player) => player,
+            None => return Some(SceneSwitch::Pop),
+        };
+        let world = &ctx.world;
+        let stats = world.stats(player);
+
+        let mut lines = vec![
+            format!("Level {} ({} xp)", stats.level, stats.xp),
+            format!("Health: {}/{}", world.hp(player), world.max_hp(player)),
+            format!(
+                "Power: {}  Attack: {}  Defense: {}  Armor: {}",
+                world.power(player),
+                world.attack(player),
+                world.defense(player),
+                stats.armor
+            ),
+            String::new(),
+            "Perks:".to_string(),
+        ];
+        if world.perks().is_empty() {
+            lines.push("  none".to_string());
+        }
+        for perk in world.perks() {
+            lines.push(format!("  {:<12} {}", perk.name(), perk.description()));
+        }
+        lines.push(String::new());
+        lines.push("Esc: close".to_string());
+
+        let font = display::font();
+        let mut pos = canvas.draw_text(
+            &*font,
+            area.origin + vec2(8, 8),
+            Align::Left,
+            color::ORANGE,
+            &world.entity_name(player),
+        );
+        for line in &lines {
+            pos = canvas.draw_text(&*font, pos, Align::Left, color::SILVER, line);
+        }
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        _ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+            match scancode {
+                Escape | C => return Some(SceneSwitch::Pop),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// End of game summary, shown both on death and on victory.
+struct GameOverScreen;
+
+impl Scene<GameRuntime> for GameOverScreen {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        let area = canvas.screen_bounds();
+        canvas.fill_rect(&area, color::BLACK);
+
+        let (title, title_color) = match ctx.world.ending() {
+            Some(ending) if ending.victory => (format!("{} has won!", ending.name), color::GOLD),
+            Some(ending) => (format!("{} has died.", ending.name), color::RED),
+            None => ("You have died.".to_string(), color::RED),
+        };
+        let mut lines = vec![String::new()];
+        if let Some(ending) = ctx.world.ending() {
+            let stats = &ending.stats;
+            lines.push(format!("{}.", ending.cause));
+            lines.push(String::new());
+            lines.push(format!("Turns: {}", ctx.world.get_tick()));
+            lines.push(format!("Level: {} ({} xp)", stats.level, stats.xp));
+            lines.push(format!(
+                "Power: {}  Attack: {}  Defense: {}  Armor: {}",
+                stats.base_power, stats.base_attack, stats.base_defense, stats.armor
+            ));
+        }
+        lines.push(String::new());
+        lines.push("Press Enter to start a new game.".to_string());
+
+        let mut pos = canvas.draw_text(
+            &*display::font(),
+            area.origin + vec2(8, 8),
+            Align::Left,
+            title_color,
+            &title,
+        );
+        for line in &lines {
+            pos = canvas.draw_text(&*display::font(), pos, Align::Left, color::SILVER, line);
+        }
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+            match scancode {
+                Enter | PadEnter => {
+                    return Some(SceneSwitch::Replace(Box::new(CharacterCreation::default())));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Pop-up list of the items in the pile under the player.
+#[derive(Default)]
+struct PileScreen {
+    selected: usize,
+}
+
+impl PileScreen {
+    fn items(ctx: &GameRuntime) -> Vec<Entity> {
+        ctx.world
+            .player()
+            .and_then(|p| ctx.world.location(p))
+            .map_or_else(Vec::new, |loc| ctx.world.items_at(loc))
+    }
+
+    fn take(&self, ctx: &mut GameRuntime, item: Entity) -> Option<SceneSwitch<GameRuntime>> {
+        ctx.command = Some(Command::Take(item));
+        Some(SceneSwitch::Pop)
+    }
+}
+
+impl Scene<GameRuntime> for PileScreen {
+    fn render(
+        &mut self,
+        ctx: &mut GameRuntime,
+        canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        const ROW_HEIGHT: i32 = 20;
+
+        let items = Self::items(ctx);
+        if items.is_empty() {
+            return Some(SceneSwitch::Pop);
+        }
+        self.selected = self.selected.min(items.len() - 1);
+
+        let font = display::font();
+        let origin = point2(8, 8);
+        let area = Rect::new(origin, size2(320, (items.len() as i32 + 2) * ROW_HEIGHT));
+        canvas.fill_rect(&area.inflate(1, 1), color::SILVER);
+        canvas.fill_rect(&area, color::BLACK);
+
+        for (i, &item) in items.iter().enumerate() {
+            let pos = origin + vec2(4, i as i32 * ROW_HEIGHT + 2);
+            let bounds = Rect::new(pos, size2(area.size.width - 8, ROW_HEIGHT));
+            if let Some(icon) = ctx.world.entity_icon(item) {
+                canvas.draw_item_icon(pos + vec2(8, 8), icon, ctx.world.count(item));
+            }
+            let text_color = if i == self.selected {
+                color::YELLOW
+            } else {
+                color::SILVER
+            };
+            canvas.draw_text(
+                &*font,
+                pos + vec2(24, 4),
+                Align::Left,
+                text_color,
+                &ctx.world.entity_name(item),
+            );
+
+            if canvas.click_state(&bounds) == ButtonAction::LeftClicked {
+                return self.take(ctx, item);
+            }
+        }
+
+        canvas.draw_text(
+            &*font,
+            origin + vec2(4, items.len() as i32 * ROW_HEIGHT + 8),
+            Align::Left,
+            color::SILVER,
+            "Enter: take, A: take all, Esc: cancel",
+        );
+
+        None
+    }
+
+    fn input(
+        &mut self,
+        ctx: &mut GameRuntime,
+        event: &InputEvent,
+        _canvas: &mut Canvas,
+    ) -> Option<SceneSwitch<GameRuntime>> {
+        if let InputEvent::KeyEvent {
+            is_down: true,
+            hardware_key: Some(scancode),
+            ..
+        } = event
+        {
+            use Keycode::*;
+            let items = Self::items(ctx);
+            if items.is_empty() {
+                return Some(SceneSwitch::Pop);
+            }
+            let n = items.len();
+
+            match scancode {
+                Up | W => self.selected = (self.selected + n - 1) % n,
+                Down | S => self.selected = (self.selected + 1) % n,
+                Enter | PadEnter | G => return self.take(ctx, items[self.selected % n]),
+                A => {
+                    ctx.start_autopilot(Autopilot::TakeAll(usize::MAX));
+                    return Some(SceneSwitch::Pop);
+                }
+                Escape => return Some(SceneSwitch::Pop),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn draw_previous(&self) -> bool { true }
+}
+
 struct InventoryScreen;
 
 enum PickAction {
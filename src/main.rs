@@ -1,9 +1,11 @@
 // Don't show a console window when running on Windows.
 #![windows_subsystem = "windows"]
 
-use crate::game_loop::GameLoop;
+use crate::game_loop::{CharacterCreation, GameLoop};
 use log::info;
 use rand::Rng;
+use std::error::Error;
+use std::{fs, process};
 use structopt::StructOpt;
 use vitral::{AppConfig, Flick};
 use world::{ExternalEntity, WorldSeed, WorldSkeleton};
@@ -15,11 +17,31 @@ mod msg;
 struct Opt {
     #[structopt(long = "seed")]
     seed: Option<u32>,
+    /// Name of a builtin world or path to a world definition file.
+    #[structopt(long = "world", default_value = "sprawl")]
+    world: String,
+}
+
+fn load_world(name: &str) -> Result<WorldSkeleton, Box<dyn Error>> {
+    if let Some(skeleton) = WorldSkeleton::builtin(name) {
+        return Ok(skeleton);
+    }
+    fs::read_to_string(name)?.parse()
 }
 
 pub fn main() {
     let opt = Opt::from_args();
 
+    let world_skeleton = match load_world(&opt.world) {
+        Ok(skeleton) => skeleton,
+        Err(e) => {
+            let builtins: Vec<&str> = world::BUILTIN_WORLDS.iter().map(|(n, _)| *n).collect();
+            eprintln!("Couldn't load world {:?}: {}", opt.world, e);
+            eprintln!("Builtin worlds: {}", builtins.join(", "));
+            process::exit(1);
+        }
+    };
+
     const FPS: f64 = 30.0;
     env_logger::init();
 
@@ -33,7 +55,8 @@ pub fn main() {
 
     let world_seed = WorldSeed {
         rng_seed,
-        world_skeleton: WorldSkeleton::overworld_sprawl(),
+        world_skeleton,
+        // Placeholder, the real character comes from the character creation scene.
         player_character: ExternalEntity::from_name("player").unwrap(),
     };
 
@@ -41,7 +64,10 @@ pub fn main() {
         AppConfig::new(format!("Magog v{}", env!("CARGO_PKG_VERSION")))
             .frame_duration(Flick::from_seconds(1.0 / FPS)),
         game_loop::GameRuntime::new(world_seed),
-        vec![Box::new(GameLoop::default())],
+        vec![
+            Box::new(GameLoop::default()),
+            Box::new(CharacterCreation::default()),
+        ],
     )
     .run()
 }
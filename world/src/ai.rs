@@ -5,7 +5,7 @@ use crate::{
     stats::{Intrinsic, Status},
     Location, World,
 };
-use calx::{Dir6, RngExt};
+use calx::{Dir6, HexGeom, RngExt};
 use calx_ecs::Entity;
 use serde_derive::{Deserialize, Serialize};
 
@@ -152,8 +152,11 @@ impl World {
     /// Approach and attack target entity.
     fn ai_hunt(&mut self, npc: Entity, target: Entity) {
         if let (Some(my_loc), Some(target_loc)) = (self.location(npc), self.location(target)) {
-            if my_loc.metric_distance(target_loc) == 1 {
-                let _ = self.entity_melee(npc, my_loc.dir6_towards(target_loc).unwrap());
+            let adjacent = self
+                .chart_v2(self.chart_viewer(npc), my_loc, target_loc)
+                .filter(|v| v.hex_dist() == 1);
+            if let Some(v) = adjacent {
+                let _ = self.entity_melee(npc, Dir6::from_v2(v));
             } else if let Some(move_dir) = self.pathing_dir_towards(npc, target_loc) {
                 let _ = self.entity_step(npc, move_dir);
             } else {
@@ -197,18 +200,14 @@ impl World {
             self.player(),
             self.player().map(|p| self.location(p)).unwrap_or(None),
         ) {
-            if self.player_sees(loc) {
-                // Okay, tricky spot. Player might be seeing mob across a portal, in
-                // which case we can't do naive distance check.
-                // This could have a helper method that finds chart distance to self in
-                // player's map memory.
-                //
-                // For now, let's just go with mobs past portals not waking up to
-                // player.
-
-                if loc.metric_distance(player_loc) <= WAKEUP_DISTANCE {
-                    return Some(player);
-                }
+            // Player might be seeing the mob across a portal, so use chart distance instead of
+            // naive location distance.
+            if self.player_sees(loc)
+                && self
+                    .chart_distance(player, loc, player_loc)
+                    .map_or(false, |d| d <= WAKEUP_DISTANCE)
+            {
+                return Some(player);
             }
         }
         None
@@ -422,7 +421,11 @@ impl World {
         // Could do all sorts of cool things here eventually like a Dijkstra map cache, but for now
         // just doing very simple stuff.
         if let Some(origin) = self.location(e) {
-            if let Some(dir) = origin.dir6_towards(destination) {
+            let viewer = self.chart_viewer(e);
+            if let Some(dir) = self
+                .chart_v2(viewer, origin, destination)
+                .map(Dir6::from_v2)
+            {
                 // Try direct approach, the the other directions.
                 for &turn in &[0, 1, -1, 2, -2, 3] {
                     let dir = dir + turn;
@@ -0,0 +1,139 @@
+//! Player character backgrounds for character creation.
+
+use crate::{
+    desc::{Desc, Icon},
+    stats::{Intrinsic, Stats, StatsComponent},
+    Ability, ExternalEntity, Slot,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::slice;
+
+/// Starting profession of the player character.
+///
+/// The background decides the character's starting stats, intrinsics, innate abilities and kit.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Background {
+    Fighter,
+    Mage,
+    Scout,
+}
+
+impl Background {
+    pub fn iter() -> slice::Iter<'static, Background> {
+        use Background::*;
+        static BACKGROUNDS: [Background; 3] = [Fighter, Mage, Scout];
+
+        BACKGROUNDS.iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        use Background::*;
+        match self {
+            Fighter => "fighter",
+            Mage => "mage",
+            Scout => "scout",
+        }
+    }
+
+    /// Short description of the background for the character creation screen.
+    pub fn description(self) -> &'static str {
+        use Background::*;
+        match self {
+            Fighter => "Tough and well armored, starts with sword, helmet and armor.",
+            Mage => "Frail, but calls lightning at will and starts with magic wands.",
+            Scout => "Quick on their feet, starts with a bow and a club.",
+        }
+    }
+
+    fn stats(self) -> Stats {
+        use Background::*;
+        use Intrinsic::*;
+        match self {
+            Fighter => Stats::new(12, &[Hands]).defense(2),
+            Mage => Stats::new(8, &[Hands]),
+            Scout => Stats::new(10, &[Hands, Quick]),
+        }
+    }
+
+    /// Abilities the character can use without items, with a rest in between uses.
+    fn abilities(self) -> &'static [Ability] {
+        match self {
+            Background::Mage => &[Ability::LightningBolt],
+            _ => &[],
+        }
+    }
+
+    /// Starting items and the slots they go in.
+    fn kit(self) -> &'static [(Slot, &'static str)] {
+        use Background::*;
+        use Slot::*;
+        match self {
+            Fighter => &[(RightHand, "sword"), (Head, "helmet"), (Body, "armor")],
+            Mage => &[
+                (Bag(0), "wand of fireball"),
+                (Bag(1), "wand of confusion"),
+                (Bag(2), "scroll of lightning"),
+            ],
+            Scout => &[(Ranged, "bow"), (RightHand, "club")],
+        }
+    }
+
+    /// Build the starting player character with the given name.
+    pub fn player_character(self, name: &str) -> ExternalEntity {
+        let mut ret = ExternalEntity::from_name("player").unwrap();
+        ret.loadout.desc = Some(Desc::new(name, Icon::Player));
+        ret.loadout.stats = Some(StatsComponent::new(self.stats()));
+        if !self.abilities().is_empty() {
+            ret.loadout.innate_abilities = Some(self.abilities().to_vec());
+        }
+
+        for &(slot, item) in self.kit() {
+            let item = ExternalEntity::from_name(item)
+                .unwrap_or_else(|_| panic!("Background {:?} has unknown item {}", self, item));
+            ret.contents.insert(slot, item);
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Background;
+    use crate::{
+        stats::{Intrinsic, Status},
+        world::test::test_seed,
+        Ability, Command, World, WorldSeed,
+    };
+    use calx::Incremental;
+
+    #[test]
+    fn test_backgrounds() {
+        for &background in Background::iter() {
+            let pc = background.player_character("Rincewind");
+            assert_eq!(pc.loadout.desc.as_ref().unwrap().singular_name, "Rincewind");
+            assert!(!pc.contents.is_empty());
+
+            let stats = pc.loadout.stats.unwrap().base;
+            assert!(stats.intrinsics & (1 << Intrinsic::Hands as u32) != 0);
+        }
+    }
+
+    #[test]
+    fn test_innate_ability() {
+        let mut w = World::new(&WorldSeed {
+            player_character: Background::Mage.player_character("Rincewind"),
+            ..test_seed()
+        });
+        let player = w.player().unwrap();
+        assert_eq!(w.list_abilities(player), vec![Ability::LightningBolt]);
+
+        // Innate abilities need rest after use.
+        w.update(&Command::UntargetedAbility {
+            ability: Ability::LightningBolt,
+            item: None,
+        });
+        assert!(w.has_status(player, Status::Exhausted));
+        assert_eq!(w.use_ability(player, Ability::LightningBolt), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Flat local views of the portal-connected game world
+
+use crate::{Location, World};
+use calx::{CellVector, HexGeom};
+use calx_ecs::Entity;
+use euclid::vec2;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A flat 2D chart of the world around an origin location.
+///
+/// Portals make the game world non-Euclidean, two locations that show up next to each other on
+/// screen can be on different z-levels altogether. A chart records where each location appears
+/// relative to the origin, so distances and directions can be measured the way the viewer sees
+/// them instead of with the naive `Location::v2_at`.
+#[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub struct Chart {
+    origin: Location,
+    offsets: HashMap<Location, (i32, i32)>,
+}
+
+impl Chart {
+    pub fn new(origin: Location) -> Chart {
+        let mut ret = Chart {
+            origin,
+            offsets: HashMap::new(),
+        };
+        ret.insert(vec2(0, 0), origin);
+        ret
+    }
+
+    pub fn origin(&self) -> Location { self.origin }
+
+    /// Add a location to the chart at the given offset from origin.
+    ///
+    /// A location can show up in multiple places if it's seen through different portals. The
+    /// first position given for a location is the one that is kept, so locations should be
+    /// inserted in the order of increasing distance from origin.
+    pub fn insert(&mut self, pos: CellVector, loc: Location) {
+        self.offsets.entry(loc).or_insert((pos.x, pos.y));
+    }
+
+    pub fn contains(&self, loc: Location) -> bool { self.offsets.contains_key(&loc) }
+
+    /// Iterate the charted locations.
+    pub fn locations(&self) -> impl Iterator<Item = Location> + '_ { self.offsets.keys().cloned() }
+
+    /// Return the chart vector from origin to location if the location is charted.
+    pub fn offset(&self, loc: Location) -> Option<CellVector> {
+        self.offsets.get(&loc).map(|&(x, y)| vec2(x, y))
+    }
+
+    /// Return the chart vector between two charted locations.
+    pub fn v2_between(&self, origin: Location, target: Location) -> Option<CellVector> {
+        Some(self.offset(target)? - self.offset(origin)?)
+    }
+}
+
+impl World {
+    /// Return the chart of an entity's current field of view.
+    ///
+    /// Only entities with map memory, ie. the player, keep a chart.
+    pub fn chart(&self, viewer: Entity) -> Option<&Chart> {
+        self.ecs().map_memory.get(viewer).map(|m| &m.chart)
+    }
+
+    /// Return the entity whose chart should be used to measure things from the entity's position.
+    ///
+    /// Mobs don't keep charts of their own, they borrow the player's while the player sees them.
+    pub(crate) fn chart_viewer(&self, e: Entity) -> Entity {
+        match (self.player(), self.location(e)) {
+            (Some(player), Some(loc)) if self.player_sees(loc) => player,
+            _ => e,
+        }
+    }
+
+    /// Return the chart of the player's current field of view.
+    pub fn player_chart(&self) -> Option<&Chart> { self.chart(self.player()?) }
+
+    /// Return vector between two locations in the viewer's chart space.
+    ///
+    /// Uses the plain location difference when the viewer has no chart or the locations aren't
+    /// both in its view.
+    pub fn chart_v2(
+        &self,
+        viewer: Entity,
+        origin: Location,
+        target: Location,
+    ) -> Option<CellVector> {
+        if let Some(v) = self
+            .chart(viewer)
+            .and_then(|chart| chart.v2_between(origin, target))
+        {
+            return Some(v);
+        }
+        origin.v2_at(target)
+    }
+
+    /// Return hex distance between two locations in the viewer's chart space.
+    pub fn chart_distance(
+        &self,
+        viewer: Entity,
+        origin: Location,
+        target: Location,
+    ) -> Option<i32> {
+        self.chart_v2(viewer, origin, target).map(|v| v.hex_dist())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Chart;
+    use crate::Location;
+    use euclid::vec2;
+
+    #[test]
+    fn test_chart() {
+        let origin = Location::new(10, 10, 0);
+        let below = Location::new(-30, 5, -1);
+
+        let mut chart = Chart::new(origin);
+        chart.insert(vec2(1, 0), Location::new(11, 10, 0));
+        chart.insert(vec2(2, 0), below);
+        // Farther sighting of the same location is ignored.
+        chart.insert(vec2(5, 5), below);
+
+        assert_eq!(Some(vec2(0, 0)), chart.offset(origin));
+        assert_eq!(Some(vec2(2, 0)), chart.offset(below));
+        assert_eq!(
+            Some(vec2(1, 0)),
+            chart.v2_between(Location::new(11, 10, 0), below)
+        );
+        assert_eq!(None, chart.offset(Location::new(20, 20, 0)));
+        assert!(!chart.contains(Location::new(12, 10, 0)));
+    }
+}
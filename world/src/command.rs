@@ -1,4 +1,4 @@
-use crate::{Ability, Slot, World, WorldSeed};
+use crate::{Ability, Location, Perk, Slot, World, WorldSeed};
 use calx::Dir6;
 use calx::Incremental;
 use calx_ecs::Entity;
@@ -22,10 +22,8 @@ pub enum Command {
     Step(Dir6),
     /// Melee attack in direction.
     Melee(Dir6),
-    /// Pick up the topmost item from the floor where you're standing on.
-    ///
-    /// TODO: Item selection support.
-    Take,
+    /// Pick up an item from the floor where you're standing on.
+    Take(Entity),
     /// Drop an item from inventory slot.
     Drop(Slot),
     /// Equip or unequip an item in slot.
@@ -48,6 +46,18 @@ pub enum Command {
         dir: Dir6,
         item: Option<Entity>,
     },
+    /// Use an action aimed at a location that may be invoked via an item.
+    LocationTargetedAbility {
+        ability: Ability,
+        target: Location,
+        item: Option<Entity>,
+    },
+    /// Take one step towards unexplored areas or pick up an item found along the way.
+    ///
+    /// Does not take time if there are hostiles in view.
+    AutoExplore,
+    /// Choose a perk for a gained level.
+    ChoosePerk(Perk),
 }
 
 impl Incremental for World {
@@ -60,6 +70,7 @@ impl Incremental for World {
         if self.player_can_act() {
             debug_assert!(*e != Command::Wait, "Calling wait during player's turn");
             self.process_cmd(e);
+            self.check_victory();
         } else {
             debug_assert!(*e == Command::Wait, "Giving inputs outside player's turn");
         }
@@ -75,7 +86,7 @@ impl World {
     pub fn can_command(&self, cmd: &Command) -> bool {
         use Command::*;
 
-        if self.player().is_none() {
+        if self.game_over() {
             return *cmd == Command::Wait;
         }
         let player = self.player().unwrap();
@@ -114,6 +125,18 @@ impl World {
                 true
             }
 
+            // Don't let the player blast their own location.
+            LocationTargetedAbility { target, .. } => self.location(player) != Some(*target),
+
+            // Must be lying on the floor under the player, not carried.
+            Take(item) => self
+                .location(player)
+                .map_or(false, |loc| self.items_at(loc).contains(item)),
+
+            AutoExplore => self.can_autoexplore(player),
+
+            ChoosePerk(perk) => self.available_perks().contains(perk),
+
             // TODO: Add failure checks for the rest as needed.
             _ => true,
         }
@@ -135,14 +158,20 @@ impl World {
                 let player = self.player()?;
                 self.entity_melee(player, *dir)
             }
-            Take => {
+            Take(item) => {
                 let player = self.player()?;
-                let item = self.item_at(self.location(player)?)?;
-                self.entity_take(player, item)
+                if !self.can_command(cmd) {
+                    return None;
+                }
+                self.entity_take(player, *item)
             }
             Drop(slot) => {
                 let player = self.player()?;
-                self.place_entity(self.entity_equipped(player, *slot)?, self.location(player)?);
+                let item = self.entity_equipped(player, *slot)?;
+                self.place_entity(item, self.location(player)?);
+                if let Some(i) = self.ecs_mut().item.get_mut(item) {
+                    i.discarded = true;
+                }
                 // Dropping items does not cost a turn since you'll be doing it from the inventory
                 // screen.
                 Some(false)
@@ -222,6 +251,65 @@ impl World {
                     self.use_targeted_ability(player, *ability, *dir)
                 }
             }
+
+            LocationTargetedAbility {
+                ability,
+                target,
+                item,
+            } => {
+                debug_assert!(ability.is_targeted());
+                let player = self.player()?;
+                if !self.can_command(cmd) {
+                    return None;
+                }
+                // Innate targeted abilities aren't implemented yet.
+                let item = (*item)?;
+                self.use_location_targeted_item_ability(player, item, *ability, *target)
+            }
+
+            AutoExplore => {
+                let player = self.player()?;
+                self.autoexplore(player)
+            }
+
+            ChoosePerk(perk) => self.choose_perk(*perk),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Command;
+    use crate::{world::test::test_world, ExternalEntity};
+    use calx::Incremental;
+
+    #[test]
+    fn test_take() {
+        let mut w = test_world();
+        let player = w.player().unwrap();
+        let loc = w.location(player).unwrap();
+        let club = ExternalEntity::from_name("club").unwrap();
+
+        // Items spread out when spawned, keep spawning until they run out of room and pile up.
+        let first = w.spawn(&club, loc);
+        let second = w.spawn(&club, loc);
+        let mut last = second;
+        while w.items_at(loc).len() < 2 {
+            last = w.spawn(&club, loc);
+        }
+        assert_eq!(w.items_at(loc), vec![first, last]);
+
+        // Items somewhere else can't be taken.
+        assert_ne!(w.location(second), Some(loc));
+        assert!(!w.can_command(&Command::Take(second)));
+
+        // Pick the second item from the pile.
+        assert!(w.can_command(&Command::Take(last)));
+        w.update(&Command::Take(last));
+        assert_eq!(w.items_at(loc), vec![first]);
+        assert!(w.entity_contains(player, last));
+
+        // Carried items can't be taken.
+        assert!(!w.can_command(&Command::Take(last)));
+    }
+}
@@ -1,13 +1,20 @@
+use crate::chart::Chart;
+use crate::desc::Icon;
 use crate::location::Location;
 use crate::location_set::LocationSet;
 use crate::FovStatus;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Map field-of-view and remembered terrain.
 #[derive(Clone, Debug, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct MapMemory {
     pub seen: LocationSet,
     pub remembered: LocationSet,
+    /// Chart of the currently seen locations.
+    pub chart: Chart,
+    /// Static entities as they were when their location was last seen.
+    pub entities: BTreeMap<Location, Vec<RememberedEntity>>,
 }
 
 impl MapMemory {
@@ -21,3 +28,11 @@ impl MapMemory {
         }
     }
 }
+
+/// Last seen appearance of an entity in map memory.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RememberedEntity {
+    pub icon: Icon,
+    pub name: String,
+    pub count: u32,
+}
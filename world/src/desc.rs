@@ -1,6 +1,8 @@
 use crate::{
-    grammar::{GrammarPart, Noun, Pronoun},
-    World,
+    ai::Alignment,
+    grammar::{capitalize, GrammarPart, Noun, Pronoun},
+    stats::Status,
+    FovStatus, ItemType, Location, World,
 };
 use calx_ecs::Entity;
 use serde_derive::{Deserialize, Serialize};
@@ -49,6 +51,9 @@ pub enum Icon {
     Wand1,
     Wand2,
     Scroll1,
+    Key,
+    Corpse,
+    Crown,
 }
 
 /// Entity name and appearance.
@@ -57,6 +62,12 @@ pub struct Desc {
     pub singular_name: String,
     pub plural_name: Option<String>,
     pub icon: Icon,
+    /// Modifier shown before the name, such as the "+2" of an enchanted item.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Modifier shown after the name, such as the "of speed" of a magic item.
+    #[serde(default)]
+    pub suffix: Option<String>,
 }
 
 impl Desc {
@@ -89,9 +100,37 @@ impl Desc {
             singular_name,
             plural_name,
             icon,
+            prefix: None,
+            suffix: None,
         }
     }
 
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Desc {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Desc {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Return the singular or plural name with the modifiers attached.
+    pub fn full_name(&self, plural: bool) -> String {
+        let mut ret = if plural {
+            self.plural_name()
+        } else {
+            self.singular_name.clone()
+        };
+        if let Some(prefix) = &self.prefix {
+            ret = format!("{} {}", prefix, ret);
+        }
+        if let Some(suffix) = &self.suffix {
+            ret = format!("{} {}", ret, suffix);
+        }
+        ret
+    }
+
     pub fn plural_name(&self) -> String {
         if let Some(plural) = &self.plural_name {
             plural.clone()
@@ -115,9 +154,9 @@ impl World {
             let count = self.count(e);
 
             if count > 1 {
-                format!("{} {}", count, desc.plural_name())
+                format!("{} {}", count, desc.full_name(true))
             } else {
-                desc.singular_name.clone()
+                desc.full_name(false)
             }
         } else {
             "N/A".to_string()
@@ -142,9 +181,127 @@ impl World {
     /// Convenience method for formatted messages.
     pub fn object(&self, e: Entity) -> GrammarPart { GrammarPart::Object(self.noun(e)) }
 
+    /// Describe the terrain and the things at a location as far as the player knows.
+    ///
+    /// Out of sight locations are described from map memory and may be out of date.
+    pub fn describe_location(&self, loc: Location) -> Vec<String> {
+        let terrain = self.terrain(loc).name();
+        match self.fov_status(loc) {
+            None => vec!["You don't know what's there.".to_string()],
+            Some(FovStatus::Seen) => {
+                let mut ret = vec![format!("{}.", capitalize(terrain))];
+                for e in self.entities_at(loc) {
+                    if self.ecs().desc.contains(e) && !self.is_fx(e) {
+                        ret.push(self.describe_entity(e));
+                    }
+                }
+                ret
+            }
+            Some(FovStatus::Remembered) => {
+                let mut ret = vec![format!("You remember {} here.", terrain)];
+                for remembered in self.remembered_entities(loc) {
+                    let mut noun = Noun::new(remembered.name.clone());
+                    if remembered.count > 1 {
+                        noun = noun.plural();
+                    }
+                    ret.push(format!("You remember seeing {} here.", noun.a_name()));
+                }
+                ret
+            }
+        }
+    }
+
+    /// Describe an entity in a single sentence.
+    pub fn describe_entity(&self, e: Entity) -> String {
+        let mut details = Vec::new();
+
+        if self.is_mob(e) {
+            if !self.is_player(e) {
+                match self.alignment(e) {
+                    Some(Alignment::Enemy) => details.push("hostile".to_string()),
+                    Some(Alignment::Animal) => details.push("wild".to_string()),
+                    Some(Alignment::Player) => details.push("friendly".to_string()),
+                    None => {}
+                }
+                if !self.is_active(e) {
+                    details.push("asleep".to_string());
+                }
+            }
+
+            details.push(self.health_estimate(e).to_string());
+
+            if let Some(statuses) = self.ecs().status.get(e) {
+                for status in statuses.keys() {
+                    match status {
+                        Status::Confused => details.push("confused".to_string()),
+                        Status::Slowed => details.push("slowed".to_string()),
+                        Status::Hasted => details.push("hasted".to_string()),
+                        Status::Exhausted => details.push("exhausted".to_string()),
+                        Status::Dead | Status::Delayed => {}
+                    }
+                }
+            }
+
+            let gear: Vec<String> = self
+                .entities_in(e)
+                .into_iter()
+                .filter(|(slot, _)| slot.is_equipment_slot())
+                .map(|(_, item)| self.noun(item).a_name())
+                .collect();
+            if !gear.is_empty() {
+                details.push(format!("equipped with {}", gear.join(" and ")));
+            }
+        } else if self.is_item(e) {
+            let stats = self.base_stats(e);
+            for &(name, value) in &[
+                ("power", stats.base_power),
+                ("attack", stats.base_attack),
+                ("defense", stats.base_defense),
+                ("armor", stats.armor),
+            ] {
+                if value != 0 {
+                    details.push(format!("{} {:+}", name, value));
+                }
+            }
+
+            if let Some(ItemType::TargetedUsable(_)) = self.item_type(e) {
+                details.push(format!("{} charges", self.uses_left(e)));
+            }
+        }
+
+        let name = capitalize(&self.noun(e).a_name());
+        if details.is_empty() {
+            format!("{}.", name)
+        } else {
+            format!("{}, {}.", name, details.join(", "))
+        }
+    }
+
+    /// Return a vague description of how hurt a creature is.
+    fn health_estimate(&self, e: Entity) -> &'static str {
+        let (hp, max_hp) = (self.hp(e), self.max_hp(e).max(1));
+        if hp >= max_hp {
+            "unhurt"
+        } else if hp * 3 >= max_hp * 2 {
+            "lightly wounded"
+        } else if hp * 3 >= max_hp {
+            "wounded"
+        } else {
+            "badly wounded"
+        }
+    }
+
     /// Return the name that can be used to spawn this entity.
     pub fn spawn_name(&self, e: Entity) -> Option<&str> {
         // TODO: Create a special component for this.
+        // Corpses are named after their creature and have no spec of their own.
+        if self.item_type(e) == Some(ItemType::Corpse) {
+            return None;
+        }
+        // Player has a custom name from character creation.
+        if self.is_player(e) {
+            return Some("player");
+        }
         self.ecs()
             .desc
             .get(e)
@@ -36,4 +36,22 @@ impl Ability {
             _ => true,
         }
     }
+
+    /// Return how far the ability reaches from its user.
+    pub fn range(self) -> u32 {
+        use Ability::*;
+        match self {
+            LightningBolt => 4,
+            Fireball | Confuse => 9,
+        }
+    }
+
+    /// Return the radius of the area a targeted ability affects around its impact point.
+    pub fn radius(self) -> u32 {
+        use Ability::*;
+        match self {
+            Fireball => 1,
+            _ => 0,
+        }
+    }
 }
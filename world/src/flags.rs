@@ -1,4 +1,4 @@
-use crate::location::Location;
+use crate::{location::Location, perk::Perk, stats::Stats};
 use calx_ecs::Entity;
 use serde_derive::{Deserialize, Serialize};
 
@@ -11,4 +11,23 @@ pub struct Flags {
     /// Store the player entity here for fast access.
     pub player: Option<Entity>,
     pub depth: i32,
+    /// Set when the game is over.
+    pub ending: Option<Ending>,
+    /// Perks the player has chosen on level-ups.
+    pub perks: Vec<Perk>,
+    /// Number of level-ups the player still gets to choose a perk for.
+    pub pending_perks: u32,
+}
+
+/// How the game ended.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ending {
+    /// Name of the player character.
+    pub name: String,
+    /// Did the player win or die?
+    pub victory: bool,
+    /// Description of the cause of death or victory.
+    pub cause: String,
+    /// Player's stats at the end of the game.
+    pub stats: Stats,
 }
@@ -1,6 +1,11 @@
 //! Item and equipment logic
 
-use crate::{msg, Ability, ActionOutcome, Location, World};
+use crate::{
+    desc::{Desc, Icon},
+    msg,
+    world::Loadout,
+    Ability, ActionOutcome, ExternalEntity, Location, World,
+};
 use calx::{hex_neighbors, CellVector, HexGeom};
 use calx_ecs::Entity;
 use euclid::vec2;
@@ -73,6 +78,27 @@ pub enum ItemType {
     TargetedUsable(Ability),
     /// Consumed instantly when stepped on.
     Instant(Ability),
+    /// Opens locked doors when carried.
+    Key,
+    /// Remains of a dead creature.
+    ///
+    /// TODO: Food system, necromancy.
+    Corpse,
+}
+
+impl ItemType {
+    pub fn equip_type(self) -> Option<EquipType> {
+        use crate::ItemType::*;
+        match self {
+            MeleeWeapon => Some(EquipType::Melee),
+            RangedWeapon => Some(EquipType::Ranged),
+            Helmet => Some(EquipType::Head),
+            Armor => Some(EquipType::Body),
+            Boots => Some(EquipType::Feet),
+            Trinket => Some(EquipType::Trinket),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Copy, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -91,6 +117,9 @@ pub struct Item {
     pub item_type: ItemType,
     /// How many uses a wand or similar has left.
     pub charges: u32,
+    /// Set when the player drops the item, exploring won't go back to pick it up.
+    #[serde(default)]
+    pub discarded: bool,
 }
 
 /// An entity that can become a stack of multiple copies.
@@ -103,6 +132,12 @@ impl Default for Stacking {
     fn default() -> Self { Stacking { count: 1 } }
 }
 
+/// What a creature leaves behind when it dies.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Remains {
+    pub corpse: bool,
+}
+
 impl World {
     pub fn is_item(&self, e: Entity) -> bool { self.ecs().item.contains(e) }
 
@@ -128,6 +163,11 @@ impl World {
             panic!("Trying to pick up an entity you are inside of. This shouldn't happen");
         }
 
+        // A dropped item that gets picked up again is no longer junk.
+        if let Some(i) = self.ecs_mut().item.get_mut(item) {
+            i.discarded = false;
+        }
+
         // Item might go into a stack, look for stacks.
         if self.is_stackable(item) {
             let bag = self.entities_in_bag(e);
@@ -258,8 +298,14 @@ impl World {
     }
 
     /// Return first item at given location.
-    pub fn item_at(&self, loc: Location) -> Option<Entity> {
-        self.entities_at(loc).into_iter().find(|&e| self.is_item(e))
+    pub fn item_at(&self, loc: Location) -> Option<Entity> { self.items_at(loc).into_iter().next() }
+
+    /// Return all items at given location.
+    pub fn items_at(&self, loc: Location) -> Vec<Entity> {
+        self.entities_at(loc)
+            .into_iter()
+            .filter(|&e| self.is_item(e))
+            .collect()
     }
 
     pub fn can_drop_item_at(&self, loc: Location) -> bool {
@@ -311,16 +357,36 @@ impl World {
     }
 
     pub fn equip_type(&self, item: Entity) -> Option<EquipType> {
-        use crate::ItemType::*;
-        match self.item_type(item) {
-            Some(MeleeWeapon) => Some(EquipType::Melee),
-            Some(RangedWeapon) => Some(EquipType::Ranged),
-            Some(Helmet) => Some(EquipType::Head),
-            Some(Armor) => Some(EquipType::Body),
-            Some(Boots) => Some(EquipType::Feet),
-            Some(Trinket) => Some(EquipType::Trinket),
-            _ => None,
+        self.item_type(item).and_then(ItemType::equip_type)
+    }
+
+    /// Drop everything an entity is carrying or wearing on the ground.
+    ///
+    /// Placing items goes through `empty_item_drop_location`, so they spread around the location.
+    pub(crate) fn drop_inventory(&mut self, e: Entity, loc: Location) {
+        for (_, item) in self.entities_in(e) {
+            self.place_entity(item, loc);
+        }
+    }
+
+    /// Leave the remains of a dead creature on the ground if it has a body to leave.
+    pub(crate) fn leave_corpse(&mut self, e: Entity, loc: Location) {
+        if !self.ecs().remains.get(e).map_or(false, |r| r.corpse) {
+            return;
         }
+        let name = match self.ecs().desc.get(e) {
+            Some(desc) => format!("{} corpse", desc.singular_name),
+            None => return,
+        };
+
+        let corpse = ExternalEntity::new(Loadout::default().c(Desc::new(&name, Icon::Corpse)).c(
+            Item {
+                item_type: ItemType::Corpse,
+                charges: 0,
+                discarded: false,
+            },
+        ));
+        self.spawn(&corpse, loc);
     }
 
     pub(crate) fn drain_charge(&mut self, item: Entity) {
@@ -335,3 +401,59 @@ impl World {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ItemType;
+    use crate::{effect::Damage, spec::EntitySpawn, world::test::test_world, Location, World};
+    use calx_ecs::Entity;
+    use std::str::FromStr;
+
+    fn items_at(w: &World, loc: Location) -> Vec<Entity> {
+        w.entities_at(loc)
+            .into_iter()
+            .filter(|&e| w.item_type(e).is_some())
+            .collect()
+    }
+
+    /// Return the location of each item lying on the ground near a location.
+    fn items_near(w: &World, loc: Location) -> Vec<Location> {
+        calx::hex_disc(loc, 8)
+            .flat_map(|l| items_at(w, l).into_iter().map(move |_| l))
+            .collect()
+    }
+
+    #[test]
+    fn test_death_drops() {
+        let mut w = test_world();
+        let loc = w.location(w.player().unwrap()).unwrap();
+        let mut rng = calx::seeded_rng(&1);
+        let mut spawn = |w: &mut World, name| {
+            let spawn = EntitySpawn::from_str(name).unwrap();
+            w.spawn(&spawn.generate(&mut rng, 1), loc)
+        };
+        let corpses = |w: &World| {
+            items_near(w, loc)
+                .into_iter()
+                .flat_map(|l| items_at(w, l))
+                .filter(|&e| w.item_type(e) == Some(ItemType::Corpse))
+                .count()
+        };
+        assert!(items_near(&w, loc).is_empty());
+
+        // Sword, helmet, guaranteed scroll and the corpse, one per cell.
+        let chief = spawn(&mut w, "lizardman chief");
+        w.damage(chief, 1000, Damage::Physical, None);
+        assert!(!w.is_alive(chief));
+        let drops = items_near(&w, loc);
+        assert_eq!(drops.len(), 4);
+        assert!(drops.iter().all(|&l| items_at(&w, l).len() == 1));
+        assert_eq!(corpses(&w), 1);
+
+        // Burning leaves no corpse.
+        let dreg = spawn(&mut w, "dreg");
+        w.damage(dreg, 1000, Damage::Fire, None);
+        assert!(!w.is_alive(dreg));
+        assert_eq!(corpses(&w), 1);
+    }
+}
@@ -5,10 +5,17 @@ mod ai;
 mod animations;
 pub use animations::{Anim, AnimState, LerpLocation, PhysicsSpace, PhysicsVector};
 
+mod background;
+pub use background::Background;
+
+mod chart;
+pub use chart::Chart;
+
 mod command;
 pub use command::{ActionOutcome, Command};
 
 mod components;
+pub use components::RememberedEntity;
 
 mod desc;
 pub use desc::Icon;
@@ -20,6 +27,7 @@ mod extract;
 pub use extract::ExternalEntity;
 
 mod flags;
+pub use flags::Ending;
 
 mod fov;
 
@@ -37,6 +45,7 @@ mod mapsave;
 pub use mapsave::WorldData;
 
 mod map;
+pub use map::{Map, MapCell};
 
 mod movement;
 
@@ -45,21 +54,32 @@ pub use msg::{register_msg_receiver, MsgReceiver};
 
 mod mutate;
 
+mod perk;
+pub use perk::Perk;
+
 mod query;
 
 mod sector;
-pub use sector::{Sector, SectorDir, SectorVec, WorldSkeleton, SECTOR_HEIGHT, SECTOR_WIDTH};
+pub use sector::{
+    generate as generate_sector, Sector, SectorDir, SectorVec, WorldSkeleton, BUILTIN_WORLDS,
+    SECTOR_HEIGHT, SECTOR_WIDTH,
+};
 
 mod spatial;
 mod spec;
 mod stats;
+pub use stats::Stats;
 
 pub mod terrain;
 pub use terrain::Terrain;
 
+mod travel;
+
 mod vaults;
+pub use vaults::all_vaults;
 
 mod volume;
+pub use volume::Volume;
 
 mod world;
 pub use crate::world::{Ecs, World, WorldSeed};
@@ -128,7 +128,9 @@ impl Location {
     }
 
     /// Smooth noise offset for determinining overland cell boundaries at this location.
-    pub fn terrain_cell_displacement(self) -> CellVector {
+    ///
+    /// Different world seeds produce different boundaries.
+    pub fn terrain_cell_displacement(self, seed: u32) -> CellVector {
         use lazy_static::lazy_static;
         use noise::NoiseFn;
         lazy_static! {
@@ -138,7 +140,11 @@ impl Location {
         let (dx, dy) = {
             const ZOOM: f64 = 1.0 / 2.0;
             const SCALE: f64 = 4.0;
-            let (x, y) = (self.x as f64 * ZOOM, self.y as f64 * ZOOM);
+            // Shift to a seed-specific part of the noise plane.
+            let (x, y) = (
+                self.x as f64 * ZOOM + (seed % 4001) as f64 * 17.0,
+                self.y as f64 * ZOOM + (seed % 3989) as f64 * 19.0,
+            );
             // Use 3D hex coordinates to get a symmetric kernel.
             // Sample the different components from different places in the noise plane.
             let dx = SCALE * NOISE.get([x, y]);
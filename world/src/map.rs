@@ -1,9 +1,9 @@
 //! Map generation for individual sectors
 
 use crate::mapsave::{self, MapPatch};
-use crate::spec::EntitySpawn;
+use crate::spec::{EntitySpawn, SpawnSlot};
 use crate::terrain::Terrain;
-use calx::{die, CellVector, DenseTextMap, Dir6, HexGeom, IntoPrefab, Noise};
+use calx::{die, CellVector, DenseTextMap, Dir6, HexGeom, HexTransform, IntoPrefab, Noise};
 use euclid::vec2;
 use indexmap::{IndexMap, IndexSet};
 use log::Level::Trace;
@@ -14,7 +14,6 @@ use rand::Rng;
 use std::error::Error;
 use std::fmt;
 use std::ops::Index;
-use std::str::FromStr;
 use std::u32;
 
 // NOTE ON STABLE ORDER
@@ -29,6 +28,10 @@ use std::u32;
 pub struct Map {
     contents: IndexMap<CellVector, MapCell>,
     player_entrance: Option<CellVector>,
+    /// Name of a prefab vault.
+    name: Option<String>,
+    /// Names of the vaults placed on this map.
+    placed_vaults: Vec<String>,
 }
 
 impl<'a> From<&'a Map> for mapsave::Prefab {
@@ -63,6 +66,14 @@ impl Map {
 
     pub fn get(&self, pos: CellVector) -> Option<&MapCell> { self.contents.get(&pos) }
 
+    /// Remove the spawn slots from the map and return them.
+    pub fn take_spawn_slots(&mut self) -> Vec<(CellVector, SpawnSlot)> {
+        self.contents
+            .iter_mut()
+            .filter_map(|(&pos, c)| c.slot.take().map(|slot| (pos, slot)))
+            .collect()
+    }
+
     /// Return either the designated entry point or an arbitrary deterministic one.
     pub fn player_entrance(&self) -> CellVector {
         self.player_entrance.unwrap_or_else(|| {
@@ -75,6 +86,17 @@ impl Map {
         })
     }
 
+    /// Return the map with a name that shows up in `placed_vaults` when it's placed as a room.
+    pub fn named(mut self, name: impl Into<String>) -> Map {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> { self.name.as_deref() }
+
+    /// Return the names of the named rooms that have been placed on this map.
+    pub fn placed_vaults(&self) -> &[String] { &self.placed_vaults }
+
     /// Build a map with a shaped base of filled with the given terrain
     pub fn new_base(terrain: Terrain, points: impl IntoIterator<Item = CellVector>) -> Map {
         let mut ret = Map::default();
@@ -178,9 +200,19 @@ impl Map {
 
                 'a' => {
                     cell.terrain = Ground;
-                    // TODO: Put generic "monster" spawns in factory, don't want all vaults to have
-                    // fixed types.
-                    cell.spawns.push(EntitySpawn::from_str("dreg").unwrap());
+                    cell.slot = Some(SpawnSlot::Monster);
+                }
+                'A' => {
+                    cell.terrain = Ground;
+                    cell.slot = Some(SpawnSlot::ToughMonster);
+                }
+                'E' => {
+                    cell.terrain = Ground;
+                    cell.slot = Some(SpawnSlot::Encounter);
+                }
+                'B' => {
+                    cell.terrain = Ground;
+                    cell.slot = Some(SpawnSlot::Boss);
                 }
 
                 c => {
@@ -221,6 +253,31 @@ impl Map {
         ret
     }
 
+    /// Return a copy of the map rotated and mirrored around the origin.
+    pub fn transformed(&self, t: HexTransform) -> Map {
+        Map {
+            contents: self
+                .contents
+                .iter()
+                .map(|(&pos, c)| (t.apply(pos), c.clone()))
+                .collect(),
+            player_entrance: self.player_entrance.map(|pos| t.apply(pos)),
+            name: self.name.clone(),
+            placed_vaults: self.placed_vaults.clone(),
+        }
+    }
+
+    /// Return the transforms this map can be placed with.
+    ///
+    /// Stairs going up must point north and stairs going down must point south, so maps with
+    /// stairwells can only be mirrored along the north-south axis.
+    pub fn orientations(&self) -> Vec<HexTransform> {
+        let has_stairs = self.iter().any(|(_, c)| c.terrain.dz() != 0);
+        HexTransform::iter()
+            .filter(|t| !has_stairs || t.keeps_north())
+            .collect()
+    }
+
     /// Return whether position is in defined area of this map.
     pub fn contains(&self, pos: CellVector) -> bool { self.contents.contains_key(&pos) }
 
@@ -367,20 +424,115 @@ impl Map {
         if let (None, Some(pos)) = (self.player_entrance, room.player_entrance) {
             self.player_entrance = Some(pos + offset);
         }
+
+        if let Some(name) = &room.name {
+            self.placed_vaults.push(name.clone());
+        }
     }
 
     /// Helper function to randomly place a room
+    ///
+    /// The room's orientations are tried in random order until one of them fits.
     pub fn place_room(
         &mut self,
         rng: &mut (impl Rng + ?Sized),
         room: &Map,
     ) -> Result<(), Box<dyn Error>> {
-        let sites = self.room_positions(room);
-        if sites.is_empty() {
-            die!("No room left");
+        let mut orientations = room.orientations();
+        orientations.shuffle(rng);
+
+        for t in orientations {
+            let room = room.transformed(t);
+            let sites = self.room_positions(&room);
+            if let Some(&site) = sites.choose(rng) {
+                self.place_room_at(site, &room);
+                return Ok(());
+            }
         }
-        self.place_room_at(*sites.choose(rng).unwrap(), room);
-        Ok(())
+        die!("No room left");
+    }
+
+    /// Carve caves into the plain rock of the map with a hex cellular automaton.
+    ///
+    /// Vault cells, stairwells and cells at the map edge are left alone. Cave pockets smaller
+    /// than `min_region` cells are filled back in so there will be fewer regions to join.
+    pub fn carve_caves(&mut self, rng: &mut (impl Rng + ?Sized), min_region: usize) {
+        const INITIAL_OPEN_CHANCE: f64 = 0.55;
+        const ITERATIONS: usize = 4;
+
+        let cells = self.find_positions(|p, c| {
+            c.vault_kind.is_none()
+                && c.can_dig
+                && !c.is_walkable()
+                && c.terrain.dz() == 0
+                && calx::hex_neighbors(p).all(|p| self.contains(p))
+        });
+
+        let mut open: IndexSet<CellVector> = cells
+            .iter()
+            .cloned()
+            .filter(|_| rng.gen_bool(INITIAL_OPEN_CHANCE))
+            .collect();
+
+        for _ in 0..ITERATIONS {
+            open = cells
+                .iter()
+                .cloned()
+                .filter(|&p| {
+                    let open_neighbors = calx::hex_neighbors(p)
+                        .filter(|q| open.contains(q) || self[*q].is_walkable())
+                        .count();
+                    // Open cells survive with three open neighbors, closed cells need four.
+                    open_neighbors >= 4 || (open_neighbors == 3 && open.contains(&p))
+                })
+                .collect();
+        }
+
+        for region in separate_regions(open) {
+            if region.len() >= min_region {
+                for p in region {
+                    self.dig(p);
+                }
+            }
+        }
+    }
+
+    /// Turn walkable cells that can't be reached from origin into the given terrain.
+    pub fn fill_unreachable(&mut self, origin: CellVector, terrain: Terrain) {
+        let floors: IndexSet<CellVector> = self
+            .find_positions(|_, c| c.is_walkable())
+            .into_iter()
+            .collect();
+
+        for region in separate_regions(floors) {
+            if region.contains(&origin) {
+                continue;
+            }
+            for p in region {
+                self.set_terrain(p, terrain);
+            }
+        }
+    }
+
+    /// Return the walkable cells in closed pockets that are cut off from the rest of the world.
+    ///
+    /// The largest walkable region is the main area of the map. Other regions that reach the map
+    /// edge may still connect to it through the neighboring sectors, the rest are stranded.
+    pub fn stranded_positions(&self) -> IndexSet<CellVector> {
+        let mut regions = self.walkable_regions();
+        if let Some(main) = (0..regions.len()).max_by_key(|&i| regions[i].len()) {
+            regions.swap_remove(main);
+        }
+
+        regions
+            .into_iter()
+            .filter(|region| {
+                !region
+                    .iter()
+                    .any(|&p| calx::hex_neighbors(p).any(|q| !self.contains(q)))
+            })
+            .flatten()
+            .collect()
     }
 
     /// Return whether a tunnel can be dug in `pos + dir` from `pos`.
@@ -474,17 +626,7 @@ impl Map {
         let mut ret = self.clone();
         // Keep looping until all disjoint regions are joined.
         loop {
-            let floors: IndexSet<CellVector> = ret
-                .contents
-                .iter()
-                .filter_map(|(&p, c)| if c.is_walkable() { Some(p) } else { None })
-                .collect();
-
-            // Remove vault interior bubbles from consideration, they can't be connected.
-            let regions: Vec<Vec<CellVector>> = separate_regions(floors)
-                .into_iter()
-                .filter(|p| !ret.is_interior_bubble(p))
-                .collect();
+            let regions = ret.walkable_regions();
 
             if regions.len() < 2 {
                 // All in order.
@@ -508,6 +650,22 @@ impl Map {
         Some(ret)
     }
 
+    /// Return the separate connected regions of walkable cells.
+    ///
+    /// Sealed vault interior bubbles are left out since they're never supposed to be connected.
+    pub fn walkable_regions(&self) -> Vec<Vec<CellVector>> {
+        let floors: IndexSet<CellVector> = self
+            .contents
+            .iter()
+            .filter_map(|(&p, c)| if c.is_walkable() { Some(p) } else { None })
+            .collect();
+
+        separate_regions(floors)
+            .into_iter()
+            .filter(|p| !self.is_interior_bubble(p))
+            .collect()
+    }
+
     /// Return if the set of points forms a "vault interior bubble".
     ///
     /// The set is assumed to be connected. An interior bubble consists entirely of cells inside a
@@ -654,6 +812,8 @@ fn cut_floodfilled_region(
 pub struct MapCell {
     pub terrain: Terrain,
     pub spawns: Vec<EntitySpawn>,
+    /// Spawn to be decided when the map is placed in a sector.
+    pub slot: Option<SpawnSlot>,
     can_dig: bool,
     vault_kind: Option<VaultKind>,
 }
@@ -663,6 +823,7 @@ impl Default for MapCell {
         MapCell {
             terrain: Terrain::Empty,
             spawns: Vec::new(),
+            slot: None,
             can_dig: true,
             vault_kind: None,
         }
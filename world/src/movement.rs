@@ -1,11 +1,15 @@
 //! Logic for movement and game world space
 use crate::{
+    msg,
+    sector::Connection,
+    spec::EntitySpawn,
     stats::{Intrinsic, Status},
-    ActionOutcome, Location, Sector, World,
+    ActionOutcome, Location, Sector, SectorDir, SectorVec, Terrain, World,
 };
 use calx::{Clamp, Dir6, RngExt};
 use calx_ecs::Entity;
 use rand::Rng;
+use std::str::FromStr;
 
 impl World {
     /// Mark an entity as dead, but don't remove it from the system yet.
@@ -31,7 +35,38 @@ impl World {
         self.after_entity_moved(e);
     }
 
-    pub(crate) fn after_entity_moved(&mut self, e: Entity) { self.do_fov(e); }
+    pub(crate) fn after_entity_moved(&mut self, e: Entity) {
+        self.do_fov(e);
+
+        // Uniques get announced when they enter player's view or the player's view moves to them.
+        if Some(e) == self.player() {
+            let mobs: Vec<Entity> = self.player_chart().map_or_else(Vec::new, |chart| {
+                chart
+                    .locations()
+                    .flat_map(|loc| self.entities_at(loc))
+                    .collect()
+            });
+            for mob in mobs {
+                self.notice_unique(mob);
+            }
+        } else {
+            self.notice_unique(e);
+        }
+    }
+
+    /// Announce a unique monster the first time the player sees it.
+    fn notice_unique(&mut self, e: Entity) {
+        if !self.is_mob(e) || !self.location(e).map_or(false, |loc| self.player_sees(loc)) {
+            return;
+        }
+        let spawn = match self.spawn_name(e).map(EntitySpawn::from_str) {
+            Some(Ok(spawn)) if spawn.is_unique() => spawn,
+            _ => return,
+        };
+        if self.met_uniques.insert(spawn) {
+            msg!("[One] [is] here!"; self.subject(e));
+        }
+    }
 
     pub(crate) fn entity_step(&mut self, e: Entity, dir: Dir6) -> ActionOutcome {
         if self.confused_move(e) {
@@ -111,6 +146,10 @@ impl World {
 
     /// Return whether the entity can occupy a location.
     pub fn can_enter(&self, e: Entity, loc: Location) -> bool {
+        if self.terrain(loc) == Terrain::LockedDoor {
+            // Locked doors only open for whoever carries the key.
+            return self.has_key_to(e, loc);
+        }
         if self.terrain(loc).is_door() && !self.has_intrinsic(e, Intrinsic::Hands) {
             // Can't open doors without hands.
             return false;
@@ -122,6 +161,10 @@ impl World {
     }
 
     pub fn can_enter_terrain(&self, e: Entity, loc: Location) -> bool {
+        if self.terrain(loc) == Terrain::LockedDoor {
+            // Locked doors only open for whoever carries the key.
+            return self.has_key_to(e, loc);
+        }
         if self.terrain(loc).is_door() && !self.has_intrinsic(e, Intrinsic::Hands) {
             // Can't open doors without hands.
             return false;
@@ -132,6 +175,35 @@ impl World {
         true
     }
 
+    /// Return the name of the key that opens the locked door at the location.
+    ///
+    /// The lock belongs to the connection towards the neighboring sector the door leads into.
+    pub fn lock_key(&self, loc: Location) -> Option<String> {
+        if self.world_cache.get_terrain(loc) != Terrain::LockedDoor {
+            return None;
+        }
+        let sector = Sector::from(loc);
+        SectorDir::horizontal().find_map(|dir| {
+            let other = sector + SectorVec::from(dir);
+            if !calx::hex_neighbors(loc).any(|loc| Sector::from(loc) == other) {
+                return None;
+            }
+            match self.world_cache.connection(sector, dir) {
+                Connection::Locked(key) => Some(key),
+                _ => None,
+            }
+        })
+    }
+
+    /// Return whether the entity carries the key to the locked door at the location.
+    pub fn has_key_to(&self, e: Entity, loc: Location) -> bool {
+        self.lock_key(loc).map_or(false, |key| {
+            self.entities_in_bag(e)
+                .into_iter()
+                .any(|(_, item)| self.spawn_name(item) == Some(&key[..]))
+        })
+    }
+
     /// Return whether the entity blocks movement of other entities.
     pub fn is_blocking_entity(&self, e: Entity) -> bool { self.is_mob(e) }
 
@@ -170,7 +242,11 @@ impl World {
     }
 
     pub fn distance_between(&self, e1: Entity, e2: Entity) -> Option<i32> {
-        self.location(e1)?.distance_from(self.location(e2)?)
+        self.chart_distance(
+            self.chart_viewer(e1),
+            self.location(e1)?,
+            self.location(e2)?,
+        )
     }
 
     pub fn sector_exists(&self, sector: Sector) -> bool { self.world_cache.sector_exists(sector) }
@@ -187,9 +263,7 @@ impl World {
         if self.is_underground(loc) {
             if let Some(player) = self.player() {
                 if let Some(player_loc) = self.location(player) {
-                    // XXX: This is going to get so messed up with portals, should be done in
-                    // player chart space, not here...
-                    if let Some(dist) = player_loc.distance_from(loc) {
+                    if let Some(dist) = self.chart_distance(player, player_loc, loc) {
                         return (0.0..=1.0).clamp(1.0 - (dist as f32 / 8.0));
                     }
                 }
@@ -200,3 +274,42 @@ impl World {
         1.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        sector::{Biome, Connection, SectorSpec},
+        ExternalEntity, Sector, SectorDir, Terrain, World, WorldSeed, WorldSkeleton,
+    };
+
+    #[test]
+    fn test_locked_door() {
+        let mut skeleton = WorldSkeleton::default();
+        let mut spec = SectorSpec {
+            biome: Biome::Grassland,
+            ..Default::default()
+        };
+        skeleton.insert(Sector::new(1, 0, 0), spec.clone());
+        spec.connections
+            .insert(SectorDir::East, Connection::Locked("silver key".into()));
+        skeleton.insert(Sector::new(0, 0, 0), spec);
+        let mut w = World::new(&WorldSeed {
+            rng_seed: 1,
+            world_skeleton: skeleton,
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        });
+        let player = w.player().unwrap();
+
+        let door = Sector::new(0, 0, 0)
+            .iter()
+            .find(|&loc| w.terrain(loc) == Terrain::LockedDoor)
+            .unwrap();
+        assert_eq!(w.lock_key(door), Some("silver key".to_string()));
+        assert!(!w.can_enter_terrain(player, door));
+
+        let key = ExternalEntity::from_name("silver key").unwrap();
+        let key = w.spawn(&key, w.location(player).unwrap());
+        w.entity_take(player, key);
+        assert!(w.can_enter_terrain(player, door));
+    }
+}
@@ -7,9 +7,10 @@ use crate::{
     sector::SECTOR_WIDTH,
     stats::Status,
     volume::Volume,
-    Ability, ActionOutcome, Anim, AnimState, Ecs, ExternalEntity, Location, Slot, World,
+    Ability, ActionOutcome, Anim, AnimState, Chart, Ecs, Ending, ExternalEntity, Location,
+    RememberedEntity, Slot, World,
 };
-use calx::Dir6;
+use calx::{CellVector, Dir6};
 use calx_ecs::Entity;
 use rand::seq::SliceRandom;
 
@@ -66,16 +67,61 @@ impl World {
                 OVERLAND_FOV_RANGE
             };
 
-            let fov = self.fov_from(origin, range);
+            let fov: Vec<(CellVector, Location)> = self.sight_field(origin, range).collect();
 
             let memory = &mut self.ecs.map_memory[e];
             memory.seen.clear();
+            memory.chart = Chart::new(origin);
 
-            for &loc in &fov {
+            for &(pos, loc) in &fov {
                 memory.seen.insert(loc);
                 memory.remembered.insert(loc);
+                memory.chart.insert(pos, loc);
             }
+
+            // Refresh the remembered appearance of static things in view.
+            for &(_, loc) in &fov {
+                let remembered: Vec<RememberedEntity> = self
+                    .entities_at(loc)
+                    .into_iter()
+                    .filter(|&x| !self.is_mob(x) && !self.is_fx(x))
+                    .filter_map(|x| {
+                        self.ecs.desc.get(x).map(|desc| RememberedEntity {
+                            icon: desc.icon,
+                            name: self.entity_name(x),
+                            count: self.count(x),
+                        })
+                    })
+                    .collect();
+
+                let entities = &mut self.ecs.map_memory[e].entities;
+                if remembered.is_empty() {
+                    entities.remove(&loc);
+                } else {
+                    entities.insert(loc, remembered);
+                }
+            }
+        }
+    }
+
+    /// End the game in victory if the player has made it to the surface with the artifact.
+    pub(crate) fn check_victory(&mut self) {
+        let player = match self.player() {
+            Some(player) if self.has_artifact(player) => player,
+            _ => return,
+        };
+        match self.location(player) {
+            Some(loc) if !self.is_underground(loc) => {}
+            _ => return,
         }
+
+        msg!("[One] escape[s] the dungeon with the crown of Magog!"; self.subject(player));
+        self.flags.ending = Some(Ending {
+            name: self.entity_name(player),
+            victory: true,
+            cause: "Escaped the dungeon with the crown of Magog".to_string(),
+            stats: self.stats(player),
+        });
     }
 
     /// Access the persistent random number generator.
@@ -216,9 +262,22 @@ impl World {
     /// is run for player and AI entities.
     pub(crate) fn heartbeat(&mut self, e: Entity) { self.tick_statuses(e); }
 
-    pub(crate) fn use_ability(&mut self, _e: Entity, _a: Ability) -> ActionOutcome {
-        // TODO
-        None
+    pub(crate) fn use_ability(&mut self, e: Entity, a: Ability) -> ActionOutcome {
+        /// How long an entity must recover after using an innate ability.
+        const INNATE_ABILITY_COOLDOWN: u32 = 20;
+
+        if !self.has_ability(e, a) {
+            return None;
+        }
+        if self.has_status(e, Status::Exhausted) {
+            if self.is_player(e) {
+                msg!("[One] [are] too exhausted."; self.subject(e));
+            }
+            return None;
+        }
+        self.cast_untargeted(e, a)?;
+        self.gain_status(e, Status::Exhausted, INNATE_ABILITY_COOLDOWN);
+        Some(true)
     }
 
     pub(crate) fn use_item_ability(
@@ -227,16 +286,21 @@ impl World {
         item: Entity,
         a: Ability,
     ) -> ActionOutcome {
-        debug_assert!(!a.is_targeted());
-        // TODO: Lift to generic ability use method
         if !self.has_ability(item, a) {
             return None;
         }
+        self.cast_untargeted(e, a)?;
+        self.drain_charge(item);
+        Some(true)
+    }
+
+    /// Produce the effect of an untargeted ability used by an entity.
+    fn cast_untargeted(&mut self, e: Entity, a: Ability) -> ActionOutcome {
+        debug_assert!(!a.is_targeted());
         let origin = self.location(e)?;
 
         match a {
             Ability::LightningBolt => {
-                const LIGHTNING_RANGE: u32 = 4;
                 const LIGHTNING_EFFECT: Effect = Effect::Hit {
                     amount: 12,
                     damage: Damage::Electricity,
@@ -245,7 +309,7 @@ impl World {
                 // TODO: Make an API, more efficient lookup of entities within an area
 
                 let targets: Vec<Entity> = self
-                    .sphere_volume(origin, LIGHTNING_RANGE)
+                    .sphere_volume(origin, a.range())
                     .0
                     .into_iter()
                     .flat_map(|loc| self.entities_at(loc))
@@ -264,7 +328,6 @@ impl World {
                 msg!("TODO cast untargeted spell {:?}", a);
             }
         }
-        self.drain_charge(item);
         Some(true)
     }
 
@@ -284,6 +347,32 @@ impl World {
         item: Entity,
         a: Ability,
         dir: Dir6,
+    ) -> ActionOutcome {
+        let origin = self.location(e)?;
+        let center = self.projected_explosion_center(origin, dir, a.range());
+        self.use_aimed_item_ability(e, item, a, center)
+    }
+
+    pub(crate) fn use_location_targeted_item_ability(
+        &mut self,
+        e: Entity,
+        item: Entity,
+        a: Ability,
+        target: Location,
+    ) -> ActionOutcome {
+        let origin = self.location(e)?;
+        let center =
+            self.projected_explosion_center_at(self.chart_viewer(e), origin, target, a.range());
+        self.use_aimed_item_ability(e, item, a, center)
+    }
+
+    /// Fire a targeted item ability that hits at center.
+    fn use_aimed_item_ability(
+        &mut self,
+        e: Entity,
+        item: Entity,
+        a: Ability,
+        center: Location,
     ) -> ActionOutcome {
         debug_assert!(a.is_targeted());
         if !self.has_ability(item, a) {
@@ -295,14 +384,11 @@ impl World {
 
         match a {
             Ability::Fireball => {
-                const FIREBALL_RANGE: u32 = 9;
-                const FIREBALL_RADIUS: u32 = 1;
                 const FIREBALL_EFFECT: Effect = Effect::Hit {
                     amount: 6,
                     damage: Damage::Fire,
                 };
-                let center = self.projected_explosion_center(origin, dir, FIREBALL_RANGE);
-                let volume = self.sphere_volume(center, FIREBALL_RADIUS);
+                let volume = self.sphere_volume(center, a.radius());
                 self.apply_effect(&FIREBALL_EFFECT, &volume, Some(e));
 
                 // TODO: Maybe move anim generation to own procedure?
@@ -322,9 +408,6 @@ impl World {
                 }
             }
             Ability::Confuse => {
-                const CONFUSION_RANGE: u32 = 9;
-
-                let center = self.projected_explosion_center(origin, dir, CONFUSION_RANGE);
                 self.apply_effect(&Effect::Confuse, &Volume::point(center), Some(e));
             }
             _ => {
@@ -0,0 +1,201 @@
+//! Character progression choices made on level-up.
+
+use crate::{msg, stats::Intrinsic, Ability, ActionOutcome, World};
+use serde_derive::{Deserialize, Serialize};
+use std::slice;
+
+/// Bonus the player picks when gaining a level.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Perk {
+    /// More power, for more health.
+    Toughness,
+    /// Better attack.
+    Might,
+    /// Better defense.
+    Agility,
+    /// Gain the Quick intrinsic.
+    Speed,
+    /// Learn to call lightning without a scroll.
+    Stormcaller,
+}
+
+impl Perk {
+    pub fn iter() -> slice::Iter<'static, Perk> {
+        use Perk::*;
+        static PERKS: [Perk; 5] = [Toughness, Might, Agility, Speed, Stormcaller];
+
+        PERKS.iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        use Perk::*;
+        match self {
+            Toughness => "toughness",
+            Might => "might",
+            Agility => "agility",
+            Speed => "speed",
+            Stormcaller => "stormcaller",
+        }
+    }
+
+    /// Short description of the perk for the level-up screen.
+    pub fn description(self) -> &'static str {
+        use Perk::*;
+        match self {
+            Toughness => "+3 power",
+            Might => "+2 attack",
+            Agility => "+2 defense",
+            Speed => "Move and act faster",
+            Stormcaller => "Call lightning on a nearby enemy at will",
+        }
+    }
+
+    /// Innate ability granted by the perk.
+    pub fn ability(self) -> Option<Ability> {
+        match self {
+            Perk::Stormcaller => Some(Ability::LightningBolt),
+            _ => None,
+        }
+    }
+
+    /// Stat boosts can be stacked, other perks can only be taken once.
+    fn is_repeatable(self) -> bool {
+        use Perk::*;
+        matches!(self, Toughness | Might | Agility)
+    }
+}
+
+impl World {
+    /// Return the perks the player has chosen, in the order they were chosen.
+    pub fn perks(&self) -> &[Perk] { &self.flags.perks }
+
+    /// Return the number of level-ups the player hasn't chosen a perk for yet.
+    pub fn pending_perks(&self) -> u32 { self.flags.pending_perks }
+
+    /// Return the perks the player can choose from right now.
+    pub fn available_perks(&self) -> Vec<Perk> {
+        let player = match self.player() {
+            Some(player) if self.pending_perks() > 0 => player,
+            _ => return Vec::new(),
+        };
+        Perk::iter()
+            .cloned()
+            .filter(|p| p.is_repeatable() || !self.perks().contains(p))
+            // No use learning an ability the background already gave.
+            .filter(|p| p.ability().map_or(true, |a| !self.has_ability(player, a)))
+            .collect()
+    }
+
+    pub(crate) fn choose_perk(&mut self, perk: Perk) -> ActionOutcome {
+        let player = self.player()?;
+        if !self.available_perks().contains(&perk) {
+            return None;
+        }
+
+        let stats = self.base_stats_mut(player)?;
+        match perk {
+            Perk::Toughness => stats.base_power += 3,
+            Perk::Might => stats.base_attack += 2,
+            Perk::Agility => stats.base_defense += 2,
+            Perk::Speed => stats.add_intrinsic(Intrinsic::Quick),
+            Perk::Stormcaller => {}
+        }
+        self.rebuild_stats(player);
+
+        // Perk abilities become innate abilities like the ones from the character's background.
+        if let Some(ability) = perk.ability() {
+            let mut abilities = self.list_abilities(player);
+            abilities.push(ability);
+            self.ecs_mut().innate_abilities.insert(player, abilities);
+        }
+
+        self.flags.pending_perks -= 1;
+        self.flags.perks.push(perk);
+        msg!("[One] gain[s] {}.", perk.name(); self.subject(player));
+
+        // Choosing doesn't take time.
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Perk;
+    use crate::{
+        stats::Status,
+        world::test::{test_seed, test_world},
+        Ability, Background, Command, World, WorldSeed,
+    };
+    use calx::Incremental;
+
+    #[test]
+    fn test_choose_perk() {
+        let mut w = test_world();
+        let player = w.player().unwrap();
+        assert_eq!(w.pending_perks(), 0);
+        assert!(!w.can_command(&Command::ChoosePerk(Perk::Might)));
+
+        w.gain_xp(player, 200);
+        assert_eq!(w.pending_perks(), 2);
+
+        let attack = w.stats(player).base_attack;
+        assert!(w.player_can_act());
+        w.update(&Command::ChoosePerk(Perk::Might));
+        // Choosing doesn't use up the player's turn.
+        assert!(w.player_can_act());
+        assert_eq!(w.pending_perks(), 1);
+        assert_eq!(w.stats(player).base_attack, attack + 2);
+
+        // Stat perks can be taken again, the others only once.
+        assert!(w.can_command(&Command::ChoosePerk(Perk::Might)));
+        w.update(&Command::ChoosePerk(Perk::Stormcaller));
+        assert_eq!(w.perks(), &[Perk::Might, Perk::Stormcaller]);
+        w.gain_xp(player, 100);
+        assert!(!w.can_command(&Command::ChoosePerk(Perk::Stormcaller)));
+        w.update(&Command::ChoosePerk(Perk::Stormcaller));
+        assert_eq!(w.pending_perks(), 1);
+
+        // The perk's innate ability needs rest after use.
+        assert!(w.list_abilities(player).contains(&Ability::LightningBolt));
+        w.update(&Command::UntargetedAbility {
+            ability: Ability::LightningBolt,
+            item: None,
+        });
+        assert!(w.has_status(player, Status::Exhausted));
+
+        // A mage already knows how to call lightning.
+        let mut w = World::new(&WorldSeed {
+            player_character: Background::Mage.player_character("Rincewind"),
+            ..test_seed()
+        });
+        let player = w.player().unwrap();
+        w.gain_xp(player, 100);
+        assert!(!w.available_perks().contains(&Perk::Stormcaller));
+    }
+
+    #[test]
+    fn test_perk_replay() {
+        let events = [
+            Command::ChoosePerk(Perk::Speed),
+            Command::ChoosePerk(Perk::Speed),
+            Command::ChoosePerk(Perk::Toughness),
+        ];
+        // Replay the same command history on fresh worlds.
+        let play = || {
+            let mut w = World::new(&test_seed());
+            let player = w.player().unwrap();
+            w.gain_xp(player, 300);
+            for e in &events {
+                w.update(e);
+            }
+            w
+        };
+
+        let (a, b) = (play(), play());
+        assert_eq!(a.perks(), &[Perk::Speed, Perk::Toughness]);
+        assert_eq!(a.pending_perks(), 1);
+        assert_eq!(a.perks(), b.perks());
+        let player = a.player().unwrap();
+        assert_eq!(a.stats(player), b.stats(b.player().unwrap()));
+    }
+}
@@ -1,9 +1,13 @@
 //! Gameplay logic that answers questions but doesn't change anything
 
 use crate::{
-    fov::SightFov, location::Location, mapsave, spec::EntitySpawn, Ecs, FovStatus, Terrain, World,
+    fov::SightFov,
+    location::Location,
+    mapsave,
+    spec::{self, EntitySpawn},
+    Ability, Ecs, Ending, FovStatus, RememberedEntity, Terrain, World,
 };
-use calx::{Dir6, HexFov, HexFovIter, Noise};
+use calx::{hex_line, CellVector, Dir6, HexFov, HexFovIter, Noise};
 use calx_ecs::Entity;
 use indexmap::IndexSet;
 use rand::distributions::Uniform;
@@ -48,9 +52,31 @@ impl World {
         Some(FovStatus::Seen)
     }
 
+    /// Return the static entities the player remembers seeing at a location.
+    pub fn remembered_entities(&self, loc: Location) -> &[RememberedEntity] {
+        if let Some(p) = self.player() {
+            if let Some(memory) = self.ecs().map_memory.get(p) {
+                if let Some(entities) = memory.entities.get(&loc) {
+                    return entities;
+                }
+            }
+        }
+        &[]
+    }
+
     /// Return true if the game has ended and the player can make no further
     /// actions.
-    pub fn game_over(&self) -> bool { self.player().is_none() }
+    pub fn game_over(&self) -> bool { self.player().is_none() || self.flags.ending.is_some() }
+
+    /// Return how the game ended if it's over.
+    pub fn ending(&self) -> Option<&Ending> { self.flags.ending.as_ref() }
+
+    /// Return whether the entity is carrying the artifact needed to win the game.
+    pub fn has_artifact(&self, e: Entity) -> bool {
+        self.entities_in(e)
+            .into_iter()
+            .any(|(_, item)| self.spawn_name(item) == Some(spec::ARTIFACT))
+    }
 
     /// Return terrain at location for drawing on screen.
     ///
@@ -140,6 +166,64 @@ impl World {
         loc
     }
 
+    /// Return the locations a projectile from origin towards target passes through.
+    ///
+    /// The line is drawn in the viewer's chart space, so it crosses portals the way they show up
+    /// on screen. It ends at target, at the first cell with a mob, before the first cell with
+    /// shot-blocking terrain or when it runs out of range.
+    pub fn line_of_fire(
+        &self,
+        viewer: Entity,
+        origin: Location,
+        target: Location,
+        range: u32,
+    ) -> Vec<Location> {
+        let mut ret = Vec::new();
+        let offsets = match self.chart_v2(viewer, origin, target) {
+            Some(v) => hex_line(v),
+            None => return ret,
+        };
+
+        let mut loc = origin;
+        for step in offsets.windows(2).take(range as usize) {
+            let new_loc = loc.jump(self, Dir6::from_v2(step[1] - step[0]));
+            if self.terrain(new_loc).blocks_shot() {
+                break;
+            }
+            ret.push(new_loc);
+            if self.has_mobs(new_loc) {
+                break;
+            }
+            loc = new_loc;
+        }
+        ret
+    }
+
+    /// Find a location for spell explosion when aiming at a target location.
+    pub fn projected_explosion_center_at(
+        &self,
+        viewer: Entity,
+        origin: Location,
+        target: Location,
+        range: u32,
+    ) -> Location {
+        self.line_of_fire(viewer, origin, target, range)
+            .last()
+            .cloned()
+            .unwrap_or(origin)
+    }
+
+    /// Return the locations a targeted ability used by the entity at target would affect.
+    pub fn ability_area(&self, e: Entity, a: Ability, target: Location) -> Vec<Location> {
+        let origin = match self.location(e) {
+            Some(loc) => loc,
+            None => return Vec::new(),
+        };
+        let center =
+            self.projected_explosion_center_at(self.chart_viewer(e), origin, target, a.range());
+        self.sphere_volume(center, a.radius()).0
+    }
+
     /// Return whether the player can currently directly see the given location.
     pub fn player_sees(&self, loc: Location) -> bool {
         self.fov_status(loc) == Some(FovStatus::Seen)
@@ -148,12 +232,19 @@ impl World {
     pub fn fov_from(&self, origin: Location, range: i32) -> IndexSet<Location> {
         // Use IndexSet as return type because eg. AI logic for dealing with seen things may depend
         // on iteration order.
+        IndexSet::from_iter(self.sight_field(origin, range).map(|(_, loc)| loc))
+    }
+
+    /// Iterate seen locations along with their chart positions relative to origin.
+    pub(crate) fn sight_field<'a>(
+        &'a self,
+        origin: Location,
+        range: i32,
+    ) -> impl Iterator<Item = (CellVector, Location)> + 'a {
         debug_assert!(range >= 0);
 
-        IndexSet::from_iter(
-            HexFov::new(SightFov::new(self, range as u32, origin))
-                .add_fake_isometric_acute_corners(|pos, a| self.terrain(a.origin + pos).is_wall())
-                .map(|(pos, a)| a.origin + pos),
-        )
+        HexFov::new(SightFov::new(self, range as u32, origin))
+            .add_fake_isometric_acute_corners(move |pos, a| self.terrain(a.origin + pos).is_wall())
+            .map(|(pos, a)| (pos, a.origin + pos))
     }
 }
@@ -3,21 +3,22 @@
 use crate::{
     location::Location,
     map::{Map, MapCell},
-    spec::{self, EntitySpawn, Spec},
+    spec::{self, EncounterSpec, EntitySpawn, SpawnSlot, Spec},
     terrain::Terrain,
     vaults, {Distribution, Rng},
 };
 use calx::{
-    die, project, seeded_rng, CellSpace, CellVector, ProjectVec, RngExt, Space, StaggeredHexSpace,
-    WeightedChoice,
+    die, err, project, seeded_rng, CellSpace, CellVector, Dir6, HexTransform, ProjectVec, RngExt,
+    Space, StaggeredHexSpace, WeightedChoice,
 };
 use euclid::{vec2, vec3, Vector2D};
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
 use log::{debug, warn};
 use rand::seq::SliceRandom;
 use rand::Rng as _;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::ops::{Add, Deref, DerefMut};
 use std::str::FromStr;
@@ -305,6 +306,30 @@ impl From<SectorDir> for SectorVec {
     }
 }
 
+impl SectorDir {
+    /// Iterate the directions to the neighboring sectors on the same level.
+    pub fn horizontal() -> impl Iterator<Item = SectorDir> {
+        use SectorDir::*;
+        [Northeast, East, Southeast, Southwest, West, Northwest]
+            .iter()
+            .cloned()
+    }
+
+    pub fn opposite(self) -> SectorDir {
+        use SectorDir::*;
+        match self {
+            Northeast => Southwest,
+            East => West,
+            Southeast => Northwest,
+            Southwest => Northeast,
+            West => East,
+            Northwest => Southeast,
+            Up => Down,
+            Down => Up,
+        }
+    }
+}
+
 /// Parts of a hex `Sector`. The `CenterRectangle` part corresponds to the game screen. The
 /// triangles are the parts of the hex above and below that.
 pub enum SectorPart {
@@ -412,6 +437,21 @@ impl Default for Biome {
 }
 
 impl Biome {
+    /// Return the biome for a character in a `WorldDefinition` overworld map.
+    pub fn from_char(c: char) -> Option<Biome> {
+        use Biome::*;
+        match c {
+            '~' => Some(Water),
+            '.' => Some(Grassland),
+            '%' => Some(Forest),
+            '^' => Some(Mountain),
+            '-' => Some(Desert),
+            '#' => Some(City),
+            '*' => Some(Dungeon),
+            _ => None,
+        }
+    }
+
     /// Return terrain for the biome at a given position.
     ///
     /// Wilderness biomes produce useful terrain via just this function. Dungeon terrains will just
@@ -420,12 +460,41 @@ impl Biome {
         use Biome::*;
 
         // Get the tile-less ones out of the way.
-        // XXX: Should Dungeon and City have herringbone sets too?
+        // XXX: Should Dungeon have herringbone sets too?
         match self {
             Dungeon => return Terrain::Rock,
-            Water => return Terrain::Water,
-            City => return Terrain::Ground,
-            Mountain => return Terrain::Rock,
+            Water => {
+                // Open water with sandbars and small islands.
+                let n = overworld_noise(seed, loc, 1.0 / 8.0);
+                return if n > 0.4 {
+                    Terrain::Grass
+                } else if n > 0.3 {
+                    Terrain::Sand
+                } else if n > 0.2 {
+                    Terrain::Shallows
+                } else {
+                    Terrain::Water
+                };
+            }
+            City => {
+                // Paved streets and grassy lots for buildings.
+                return if city_block(loc).is_some() {
+                    Terrain::Grass
+                } else {
+                    Terrain::Ground
+                };
+            }
+            Mountain => {
+                // Rock ridges with cliff faces and narrow valleys in between.
+                let n = overworld_noise(seed, loc, 1.0 / 6.0);
+                return if n > -0.15 {
+                    Terrain::Rock
+                } else if n > -0.3 {
+                    Terrain::Ground
+                } else {
+                    Terrain::Grass
+                };
+            }
             _ => {}
         }
 
@@ -455,16 +524,117 @@ impl Biome {
     }
 }
 
+/// Return smooth seeded noise for overland terrain at a location.
+fn overworld_noise(seed: u32, loc: Location, zoom: f64) -> f64 {
+    use noise::NoiseFn;
+    lazy_static! {
+        static ref NOISE: noise::OpenSimplex = noise::OpenSimplex::new();
+    }
+
+    // Move to a different part of the noise plane for each seed.
+    let (dx, dy) = ((seed % 4099) as f64 * 31.0, (seed % 4093) as f64 * 37.0);
+    NOISE.get([loc.x as f64 * zoom + dx, loc.y as f64 * zoom + dy])
+}
+
+/// Linear features that run across sector boundaries.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Crossing {
+    Road,
+    River,
+}
+
+/// Distance between the starts of two consecutive city streets.
+const CITY_BLOCK_SPAN: i32 = 12;
+
+/// Width of city streets.
+const CITY_STREET_WIDTH: i32 = 2;
+
+/// Return the city block a location is in or `None` if the location is on a street.
+///
+/// The street grid is laid in absolute coordinates, so streets line up across sectors.
+fn city_block(loc: Location) -> Option<CellVector> {
+    let (x, y) = (loc.x as i32, loc.y as i32);
+    if x.rem_euclid(CITY_BLOCK_SPAN) < CITY_STREET_WIDTH
+        || y.rem_euclid(CITY_BLOCK_SPAN) < CITY_STREET_WIDTH
+    {
+        None
+    } else {
+        Some(vec2(
+            x.div_euclid(CITY_BLOCK_SPAN),
+            y.div_euclid(CITY_BLOCK_SPAN),
+        ))
+    }
+}
+
 /// Specification for generating a Sector's map.
 ///
 /// This serves as the top-level entry point to map generation routines.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SectorSpec {
     // TODO: Sectors can be predefined maps.
-    // TODO: flags for blocked connection to N,E,W,S,up and down neighbor sectors
-    // By default create path/stairs if adjacent sector exists.
     pub depth: i32,
     pub biome: Biome,
+    /// Map generator for dungeon sectors, picked based on depth if not given.
+    #[serde(default)]
+    pub dungeon_style: Option<DungeonStyle>,
+    /// How the sector connects to its neighbors.
+    ///
+    /// Directions that aren't listed get paths and stairs if the adjacent sector exists.
+    #[serde(default)]
+    pub connections: BTreeMap<SectorDir, Connection>,
+}
+
+impl SectorSpec {
+    pub fn connection(&self, dir: SectorDir) -> Connection {
+        self.connections.get(&dir).cloned().unwrap_or_default()
+    }
+
+    pub fn dungeon_style(&self) -> DungeonStyle {
+        if let Some(style) = self.dungeon_style {
+            return style;
+        }
+
+        // Deeper levels get natural caves every now and then.
+        if self.depth >= 3 && self.depth % 2 == 1 {
+            DungeonStyle::Caves
+        } else {
+            DungeonStyle::Rooms
+        }
+    }
+}
+
+/// Passage between two neighboring sectors.
+///
+/// The variants are ordered from the least to the most restrictive. When the two sectors disagree,
+/// the more restrictive setting applies.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize, Deserialize)]
+pub enum Connection {
+    /// Connect freely if the neighbor exists.
+    Open,
+    /// Wall off the border and connect through a door.
+    ///
+    /// Only applies to sectors on the same level, stairs connect as if open.
+    Door,
+    /// Connect through a locked door that only opens for mobs carrying the named key item.
+    ///
+    /// Like doors, only applies on the same level. The key is placed in the neighboring sector,
+    /// so the sector that asks for the lock is the one behind it.
+    Locked(String),
+    /// Never connect to the neighbor.
+    Blocked,
+}
+
+impl Default for Connection {
+    fn default() -> Self { Connection::Open }
+}
+
+/// Layout style of a dungeon sector.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum DungeonStyle {
+    /// Rooms and vaults joined by tunnels.
+    Rooms,
+    /// Open natural caverns with occasional vaults.
+    Caves,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
@@ -480,85 +650,117 @@ impl DerefMut for WorldSkeleton {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
 }
 
+/// World definitions that ship with the game, by name.
+pub const BUILTIN_WORLDS: &[(&str, &str)] = &[
+    ("sprawl", include_str!("../worlds/sprawl.ron")),
+    ("dive", include_str!("../worlds/dive.ron")),
+    ("tiny", include_str!("../worlds/tiny.ron")),
+];
+
 impl WorldSkeleton {
-    pub fn dungeon_dive() -> WorldSkeleton {
-        let mut ret = WorldSkeleton::default();
-        for depth in 0..10 {
-            let sector = Sector::new(0, 0, -(depth as i16));
-            let spec = SectorSpec {
-                depth,
-                biome: Biome::Dungeon,
-                ..Default::default()
-            };
-            ret.insert(sector, spec);
-        }
-        ret
+    /// Load one of the `BUILTIN_WORLDS`.
+    pub fn builtin(name: &str) -> Option<WorldSkeleton> {
+        BUILTIN_WORLDS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, data)| data.parse().expect("Invalid builtin world definition"))
     }
 
-    pub fn overworld_sprawl() -> WorldSkeleton {
-        // FIXME
+    pub fn dungeon_dive() -> WorldSkeleton { WorldSkeleton::builtin("dive").unwrap() }
+
+    pub fn overworld_sprawl() -> WorldSkeleton { WorldSkeleton::builtin("sprawl").unwrap() }
+}
+
+impl FromStr for WorldSkeleton {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let definition: WorldDefinition = ron::de::from_str(s)?;
+        definition.build()
+    }
+}
+
+/// Data file description of a world.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorldDefinition {
+    /// Biome map of the surface level.
+    ///
+    /// The starting sector is marked with brackets. See `Biome::from_char` for the biome
+    /// characters.
+    pub overworld: String,
+    /// Dungeons below surface sectors.
+    #[serde(default)]
+    pub branches: Vec<BranchDefinition>,
+    /// Depth of the starting sector, -1 for a sector with no spawns if not given.
+    #[serde(default)]
+    pub start_depth: Option<i32>,
+}
+
+/// Dungeon branch in a `WorldDefinition`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BranchDefinition {
+    /// Position of the surface sector the branch is entered from.
+    pub entrance: (i16, i16),
+    /// Number of levels going down from the entrance.
+    pub levels: i16,
+    /// Depth of the first level, one deeper than the entrance sector if not given.
+    #[serde(default)]
+    pub depth: Option<i32>,
+    /// Map generator for the levels, picked based on depth if not given.
+    #[serde(default)]
+    pub dungeon_style: Option<DungeonStyle>,
+}
+
+impl WorldDefinition {
+    pub fn build(&self) -> Result<WorldSkeleton, Box<dyn Error>> {
         use calx::IntoPrefab;
-        const OVERWORLD_MAP: &str = "
-             ~ ~ ~ ~ ~ ~ ^ ^ ^ ^
-            ~ ~ ~ ~ . % % - - ^
-             ~ ~ . # . . % - - ^
-            ~ . . .[.]. . . - ^
-             ~ . . . . % . - - ^
-            ~ . . . . . . . . ^
-             ~ . . . # # . . . ^
-            ~ ~ . . # . . . . ^
-             ~ ~ . . . . . . . ^
-            ~ ~ ~ ~ ~ ~ ~ ^ ^ ^";
-
-        let map: HashMap<CellVector, Biome> = OVERWORLD_MAP
-            .into_prefab::<HashMap<CellVector, char>>()
-            .expect("Invalid overworld map")
-            .into_iter()
-            .map(|(p, c)| {
-                (
-                    p,
-                    match c {
-                        '~' => Biome::Water,
-                        '-' => Biome::Desert,
-                        '.' => Biome::Grassland,
-                        '%' => Biome::Forest,
-                        '#' => Biome::City,
-                        '^' => Biome::Mountain,
-                        _ => panic!("Unknown biome char {}", c),
-                    },
-                )
-            })
-            .collect();
+
+        let map: HashMap<CellVector, char> = self.overworld.as_str().into_prefab()?;
+        if !map.contains_key(&vec2(0, 0)) {
+            die!("Overworld has no starting sector");
+        }
 
         let mut ret = WorldSkeleton::default();
-        // Overworld
-        for (p, biome) in &map {
+        for (p, c) in &map {
+            let biome = Biome::from_char(*c).ok_or_else(|| err!("Unknown biome char {:?}", c))?;
             let depth = if *p == vec2(0, 0) {
-                // No spawns in entrance sector.
-                -1
+                // No spawns in entrance sector by default.
+                self.start_depth.unwrap_or(-1)
             } else {
                 (p.x.abs() + p.y.abs()) / 2
             };
-            let sector = Sector::new(p.x as i16, p.y as i16, 0);
             let spec = SectorSpec {
                 depth,
-                biome: *biome,
+                biome,
+                ..Default::default()
             };
-            ret.insert(sector, spec);
+            ret.insert(Sector::new(p.x as i16, p.y as i16, 0), spec);
         }
 
-        // Dungeons
-        for depth in 0..10 {
-            let sector = Sector::new(0, 0, -(depth as i16 + 1));
-            let spec = SectorSpec {
-                depth,
-                biome: Biome::Dungeon,
-                ..Default::default()
-            };
-            ret.insert(sector, spec);
+        for branch in &self.branches {
+            let entrance = Sector::new(branch.entrance.0, branch.entrance.1, 0);
+            let entrance_depth = ret
+                .get(&entrance)
+                .ok_or_else(|| err!("Branch entrance {:?} not in overworld", branch.entrance))?
+                .depth;
+            let depth = branch.depth.unwrap_or(entrance_depth + 1);
+
+            for i in 0..branch.levels {
+                let sector = entrance + vec3(0, 0, -(i + 1));
+                if ret.contains_key(&sector) {
+                    die!("Branch at {:?} overlaps another branch", branch.entrance);
+                }
+                let spec = SectorSpec {
+                    depth: depth + i as i32,
+                    biome: Biome::Dungeon,
+                    dungeon_style: branch.dungeon_style,
+                    ..Default::default()
+                };
+                ret.insert(sector, spec);
+            }
         }
 
-        ret
+        Ok(ret)
     }
 }
 
@@ -602,10 +804,14 @@ impl<'a> Deref for ConnectedSectorSpec<'a> {
 
 impl<'a> Distribution<Map> for ConnectedSectorSpec<'a> {
     fn sample(&self, rng: &mut Rng) -> Map {
-        match self.biome {
+        let mut map = match self.biome {
             Biome::Dungeon => self.build_dungeon(rng),
+            Biome::City => self.build_city(rng),
             _ => self.build_biome_sample_map(rng),
-        }
+        };
+        self.populate(rng, &mut map);
+        self.place_keys(rng, &mut map);
+        map
     }
 }
 
@@ -634,6 +840,8 @@ impl<'a> ConnectedSectorSpec<'a> {
         let mut map = self.dungeon_base_map();
 
         self.place_stairs(rng, &mut map)?;
+        // Boss vault goes in first so it's guaranteed to fit.
+        self.place_boss_vault(rng, &mut map)?;
 
         loop {
             let room: Room = self.sample(rng);
@@ -650,19 +858,72 @@ impl<'a> ConnectedSectorSpec<'a> {
         }
     }
 
+    fn cave_gen(&self, rng: &mut Rng) -> Result<Map, Box<dyn Error>> {
+        const NUM_VAULTS: usize = 2;
+        const MIN_CAVE_SIZE: usize = 12;
+
+        debug!("Starting cave mapgen");
+        let mut map = self.dungeon_base_map();
+
+        self.place_stairs(rng, &mut map)?;
+        self.place_boss_vault(rng, &mut map)?;
+
+        for _ in 0..NUM_VAULTS {
+            let vault = vaults::VAULTS.choose(rng).unwrap();
+            if map.place_room(rng, &*vault).is_err() {
+                break;
+            }
+        }
+
+        map.carve_caves(rng, MIN_CAVE_SIZE);
+
+        let mut map = if let Some(map) = map.join_disjoint_regions(rng) {
+            map
+        } else {
+            die!("Failed to join caves");
+        };
+
+        for pos in map.open_ground() {
+            if !map[pos].spawns.is_empty() {
+                continue;
+            }
+            if let Some(spawn) = self.sample(rng) {
+                map.push_spawn(pos, spawn);
+            }
+        }
+
+        Ok(map)
+    }
+
     pub fn neighbor(&self, offset: impl Into<SectorVec>) -> Option<&SectorSpec> {
         self.skeleton.get(&(self.sector + offset.into()))
     }
 
+    /// Return the connection to the neighbor in the given direction.
+    ///
+    /// Both sectors get a say and the more restrictive connection wins, so the maps on the two
+    /// sides will agree. A missing neighbor is always blocked.
+    pub fn connection(&self, dir: SectorDir) -> Connection {
+        match self.neighbor(dir) {
+            Some(other) => self
+                .spec
+                .connection(dir)
+                .max(other.connection(dir.opposite())),
+            None => Connection::Blocked,
+        }
+    }
+
+    fn is_connected(&self, dir: SectorDir) -> bool { self.connection(dir) != Connection::Blocked }
+
     fn place_stairs(&self, rng: &mut Rng, map: &mut Map) -> Result<(), Box<dyn Error>> {
         // TODO: Biome affects vault distribution
-        if self.neighbor(SectorDir::Up).is_some() {
+        if self.is_connected(SectorDir::Up) {
             let room: Entrance = self.sample(rng);
             debug!("Placing upstairs");
             map.place_room(rng, &*room.0)?;
         }
 
-        if self.neighbor(SectorDir::Down).is_some() {
+        if self.is_connected(SectorDir::Down) {
             // TODO: Make exit use a sampled type like Entrance does
             debug!("Placing downstairs");
             let room = vaults::EXITS.choose(rng).unwrap();
@@ -671,10 +932,24 @@ impl<'a> ConnectedSectorSpec<'a> {
         Ok(())
     }
 
+    /// Place a vault for the unique monster guarding this depth if there is one.
+    fn place_boss_vault(&self, rng: &mut Rng, map: &mut Map) -> Result<(), Box<dyn Error>> {
+        if spec::boss_at(self.depth).is_some() {
+            debug!("Placing boss vault");
+            let vault = vaults::BOSS_VAULTS.choose(rng).unwrap();
+            map.place_room(rng, &*vault)?;
+        }
+        Ok(())
+    }
+
     fn build_dungeon(&self, rng: &mut Rng) -> Map {
         const NUM_RETRIES: usize = 16;
 
-        if let Ok(map) = calx::retry_gen(NUM_RETRIES, rng, |rng| self.dungeon_gen(rng)) {
+        let style = self.dungeon_style();
+        if let Ok(map) = calx::retry_gen(NUM_RETRIES, rng, |rng| match style {
+            DungeonStyle::Rooms => self.dungeon_gen(rng),
+            DungeonStyle::Caves => self.cave_gen(rng),
+        }) {
             map
         } else {
             // Fallback, couldn't generate map, let's do something foolproof.
@@ -698,26 +973,34 @@ impl<'a> ConnectedSectorSpec<'a> {
             }
         }
 
+        // No room for a vault, the boss just waits somewhere in the open.
+        if let Some(boss) = spec::boss_at(self.depth) {
+            let sites = map.find_positions(|_, c| c.spawns.is_empty() && c.is_walkable());
+            if let Some(&pos) = sites.choose(rng) {
+                map.push_spawn(pos, boss);
+            }
+        }
+
         map
     }
 
     fn downstairs_pos(&self) -> Option<CellVector> {
-        self.neighbor(SectorDir::Down).map(|_| {
-            Location::from(self.sector)
-                .v2_at(self.sector.downstairs_location(self.seed))
-                .unwrap()
-        })
+        if !self.is_connected(SectorDir::Down) {
+            return None;
+        }
+        Location::from(self.sector).v2_at(self.sector.downstairs_location(self.seed))
     }
 
     fn upstairs_pos(&self) -> Option<CellVector> {
-        self.neighbor(SectorDir::Up).map(|_| {
-            let mut upstairs_pos = (self.sector + vec3(0, 0, 1)).downstairs_location(self.seed);
-            upstairs_pos.z -= 1;
-            // Offset it so that the exits line up nicer.
-            upstairs_pos.x -= 1;
-            upstairs_pos.y -= 1;
-            Location::from(self.sector).v2_at(upstairs_pos).unwrap()
-        })
+        if !self.is_connected(SectorDir::Up) {
+            return None;
+        }
+        let mut upstairs_pos = (self.sector + vec3(0, 0, 1)).downstairs_location(self.seed);
+        upstairs_pos.z -= 1;
+        // Offset it so that the exits line up nicer.
+        upstairs_pos.x -= 1;
+        upstairs_pos.y -= 1;
+        Location::from(self.sector).v2_at(upstairs_pos)
     }
 
     fn place_stairwells(&self, map: &mut Map) {
@@ -738,55 +1021,485 @@ impl<'a> ConnectedSectorSpec<'a> {
         ret
     }
 
-    fn build_biome_sample_map(&self, rng: &mut Rng) -> Map {
+    /// Return the overland terrain at a location before sector features are added.
+    ///
+    /// The result only depends on the location, the world seed and the skeleton, so it can be
+    /// looked up past the sector edge and it will match the neighboring sector.
+    fn overworld_terrain(&self, loc: Location) -> Terrain {
+        // TODO: If biome changes in three neighboring cells, turn terrain to ground
+        self.blended_biome(loc).terrain_at(self.seed, loc)
+    }
+
+    /// Return the biome whose terrain shows up at a location.
+    ///
+    /// Sector borders are displaced with smooth noise, and cells of the neighboring biomes are
+    /// scattered into a transition zone along the border so that there is no hard edge where
+    /// one biome turns into another.
+    fn blended_biome(&self, loc: Location) -> Biome {
+        const BLEND_WIDTH: i32 = 4;
+
+        let perturbed_loc = loc + loc.terrain_cell_displacement(self.seed);
+        let home = Sector::from(perturbed_loc);
+
+        // The closer to the border, the more likely the cell is to belong to the other side.
+        let roll: f32 = seeded_rng(&(self.seed, loc)).gen();
+        for r in 1..=BLEND_WIDTH {
+            let chance = (BLEND_WIDTH + 1 - r) as f32 / (2 * (BLEND_WIDTH + 1)) as f32;
+            if roll >= chance {
+                break;
+            }
+            let other = calx::hex_ring(perturbed_loc, r)
+                .map(Sector::from)
+                .find(|&s| s != home && self.skeleton.contains_key(&s));
+            if let Some(other) = other {
+                return self.skeleton[&other].biome;
+            }
+        }
+
+        self.skeleton
+            .get(&home)
+            .or_else(|| self.skeleton.get(&Sector::from(loc)))
+            .map_or(self.biome, |sector| sector.biome)
+    }
+
+    /// Build a map with the terrain of the sector's biome.
+    ///
+    /// Terrain from neighboring biomes will encroach near the sector edges.
+    fn biome_base_map(&self) -> Map {
         let mut map = Map::default();
         for p in self.base_shape() {
             let loc = Location::from(self.sector) + p;
-            let perturbed_loc = loc + loc.terrain_cell_displacement();
-            let mut biome = self.biome;
-            // Border noise can make neighboring sector terrain show up on this one.
-            let encroaching_sector = Sector::from(perturbed_loc);
-            if let Some(sector) = self.skeleton.get(&encroaching_sector) {
-                biome = sector.biome;
-            }
+            let mut terrain = self.overworld_terrain(loc);
 
-            // TODO: If biome changes in three neighboring cells, turn terrain to ground
-            let terrain = biome.terrain_at(self.seed, loc);
+            // Deep water turns shallow near the shore.
+            if terrain == Terrain::Water
+                && calx::hex_neighbors(loc).any(|loc| !self.overworld_terrain(loc).blocks_walk())
+            {
+                terrain = Terrain::Shallows;
+            }
 
             map.insert(p, MapCell::new_terrain(terrain));
         }
+        map
+    }
+
+    /// Return the roads and rivers that cross the edge towards a neighboring sector.
+    fn crossings(&self, dir: SectorDir) -> Vec<Crossing> {
+        use Biome::*;
+
+        let mut ret = Vec::new();
+        let connection = self.connection(dir);
+        let other = match self.neighbor(dir) {
+            Some(other) if connection != Connection::Blocked => other,
+            _ => return ret,
+        };
+        let other_sector = self.sector + SectorVec::from(dir);
+
+        // Both sectors must make the same choice, so roll the dice for the unordered pair.
+        let mut rng = seeded_rng(&(
+            self.seed,
+            self.sector.min(other_sector),
+            self.sector.max(other_sector),
+        ));
+        let road_roll = rng.one_chance_in(2);
+        let river_roll = rng.one_chance_in(4);
+
+        let biomes = [self.biome, other.biome];
+        let is_land = |b: &Biome| *b != Water && *b != Dungeon;
+
+        // Mountains always have passes to neighboring land, and doors need a road leading to them.
+        if biomes.iter().all(is_land)
+            && (road_roll || biomes.contains(&Mountain) || connection != Connection::Open)
+        {
+            ret.push(Crossing::Road);
+        }
+
+        if river_roll
+            && biomes.iter().any(is_land)
+            && !biomes.contains(&City)
+            && !biomes.contains(&Mountain)
+            && !biomes.contains(&Dungeon)
+        {
+            ret.push(Crossing::River);
+        }
+
+        ret
+    }
+
+    /// Return the line from the sector center to the edge towards a neighboring sector.
+    ///
+    /// The line ends at the midpoint between the two sector centers, where the line drawn by the
+    /// neighboring sector picks up.
+    fn edge_line(&self, dir: SectorDir) -> Vec<CellVector> {
+        let center: CellVector = vec2(SECTOR_HEX_SIDE - 1, 0);
+        let delta = SectorVec::from(dir);
+        let edge = center
+            + vec2::<i32, SectorSpace>(delta.x as i32, delta.y as i32).project::<CellSpace>() / 2;
+        calx::hex_line(edge - center)
+            .into_iter()
+            .map(|v| center + v)
+            .collect()
+    }
+
+    /// Draw roads and rivers from the center of the sector to the edges they cross.
+    fn draw_crossings(&self, map: &mut Map) {
+        let origin = Location::from(self.sector);
+        let mut rivers = Vec::new();
+        let mut roads = Vec::new();
+
+        for dir in SectorDir::horizontal() {
+            let line: Vec<CellVector> = self
+                .edge_line(dir)
+                .into_iter()
+                .filter(|&p| map.get(p).map_or(false, |c| c.terrain.dz() == 0))
+                .collect();
+
+            for crossing in self.crossings(dir) {
+                match crossing {
+                    Crossing::Road => roads.push(line.clone()),
+                    Crossing::River => rivers.push(line.clone()),
+                }
+            }
+        }
+
+        for &p in rivers.iter().flatten() {
+            // Fords let you wade across every now and then.
+            if seeded_rng(&(self.seed, origin + p)).one_chance_in(6) {
+                map.set_terrain(p, Terrain::Shallows);
+            } else {
+                map.set_terrain(p, Terrain::Water);
+            }
+        }
+
+        // Roads go over rivers on bridges.
+        for &p in roads.iter().flatten() {
+            map.set_terrain(p, Terrain::Ground);
+        }
+    }
+
+    /// Wall off the edges towards neighboring sectors that aren't openly connected.
+    ///
+    /// Gated edges get a door where the road to the neighbor crosses the wall.
+    fn draw_borders(&self, map: &mut Map) {
+        let origin = Location::from(self.sector);
+
+        for dir in SectorDir::horizontal() {
+            let connection = self.connection(dir);
+            if connection == Connection::Open || self.neighbor(dir).is_none() {
+                continue;
+            }
+
+            let other = self.sector + SectorVec::from(dir);
+            let wall = map.find_positions(|p, c| {
+                c.terrain.dz() == 0
+                    && calx::hex_neighbors(origin + p).any(|loc| Sector::from(loc) == other)
+            });
+            for &p in &wall {
+                map.set_terrain(p, Terrain::Wall);
+            }
+
+            let door = match connection {
+                Connection::Locked(_) => Terrain::LockedDoor,
+                Connection::Blocked => continue,
+                _ => Terrain::Door,
+            };
+            if let Some(p) = self.edge_line(dir).into_iter().find(|p| wall.contains(p)) {
+                map.set_terrain(p, door);
+            }
+        }
+    }
+
+    /// Place the keys to the locked connections that neighboring sectors ask for.
+    fn place_keys(&self, rng: &mut Rng, map: &mut Map) {
+        for dir in SectorDir::horizontal() {
+            let key = match self
+                .neighbor(dir)
+                .map(|other| other.connection(dir.opposite()))
+            {
+                Some(Connection::Locked(key)) => key,
+                _ => continue,
+            };
+            if let Connection::Locked(_) = self.spec.connection(dir) {
+                // Both sides asked for the lock, there's no outside to put the key in.
+                continue;
+            }
+            let spawn = match EntitySpawn::from_str(&key) {
+                Ok(spawn) => spawn,
+                Err(_) => {
+                    warn!("Unknown key {:?} for locked connection", key);
+                    continue;
+                }
+            };
+
+            let stranded = map.stranded_positions();
+            let sites = map.find_positions(|p, c| {
+                c.spawns.is_empty()
+                    && c.is_walkable()
+                    && c.terrain.dz() == 0
+                    && !stranded.contains(&p)
+            });
+            if let Some(&pos) = sites.choose(rng) {
+                map.push_spawn(pos, spawn);
+            }
+        }
+    }
+
+    fn build_biome_sample_map(&self, rng: &mut Rng) -> Map {
+        let mut map = self.biome_base_map();
+        self.draw_crossings(&mut map);
+        self.draw_borders(&mut map);
 
         // TODO: Add enclosures
         self.place_stairwells(&mut map);
 
-        for &pos in &map.open_ground() {
-            // TODO: Pick distribution based on biome...
+        if self.biome == Biome::Mountain {
+            self.connect_mountain_valleys(&mut map);
+        }
+
+        // TODO: Pick distribution based on biome...
+        self.scatter_spawns(rng, &mut map);
+
+        map
+    }
+
+    /// Make sure the passes, the cave entrance and the valleys of a mountain sector connect.
+    ///
+    /// Valleys that can't be reached from the center are filled with rock.
+    fn connect_mountain_valleys(&self, map: &mut Map) {
+        let center: CellVector = vec2(SECTOR_HEX_SIDE - 1, 0);
+        for stairs in self.downstairs_pos().into_iter().chain(self.upstairs_pos()) {
+            for v in calx::hex_line(stairs - center) {
+                let p = center + v;
+                if map.get(p).map_or(false, |c| c.terrain.blocks_walk()) {
+                    map.set_terrain(p, Terrain::Ground);
+                }
+            }
+        }
+        if map[center].terrain.blocks_walk() {
+            map.set_terrain(center, Terrain::Ground);
+        }
+        map.fill_unreachable(center, Terrain::Rock);
+    }
+
+    fn build_city(&self, rng: &mut Rng) -> Map {
+        const PLAZA_ONE_IN: u32 = 5;
+        const LOT_SIZE: i32 = CITY_BLOCK_SPAN - CITY_STREET_WIDTH;
+
+        let mut map = self.biome_base_map();
+        self.draw_crossings(&mut map);
+        self.draw_borders(&mut map);
+        self.place_stairwells(&mut map);
+
+        let origin = Location::from(self.sector);
+        let mut lots: IndexMap<CellVector, Vec<CellVector>> = IndexMap::new();
+        for (&p, _) in map.iter() {
+            if let Some(block) = city_block(origin + p) {
+                lots.entry(block).or_insert_with(Vec::new).push(p);
+            }
+        }
+
+        for lot in lots.values() {
+            // Only build on whole lots that haven't been taken over by neighboring biomes.
+            if lot.len() as i32 != LOT_SIZE * LOT_SIZE
+                || lot.iter().any(|&p| map[p].terrain != Terrain::Grass)
+            {
+                continue;
+            }
+            let lot_min = vec2(
+                lot.iter().map(|p| p.x).min().unwrap(),
+                lot.iter().map(|p| p.y).min().unwrap(),
+            );
+
+            if rng.one_chance_in(PLAZA_ONE_IN) {
+                for &p in lot {
+                    map.set_terrain(p, Terrain::Ground);
+                }
+                // Fountain in the middle.
+                let center = lot_min + vec2(LOT_SIZE / 2, LOT_SIZE / 2);
+                for p in calx::hex_neighbors(center) {
+                    map.set_terrain(p, Terrain::Shallows);
+                }
+                map.set_terrain(center, Terrain::Water);
+                continue;
+            }
+
+            // Buildings can be turned around, but the walls must stay on the fake-isometric axes.
+            let transform =
+                HexTransform::new(*[Dir6::North, Dir6::South].choose(rng).unwrap(), rng.gen());
+            let building = vaults::BUILDINGS
+                .choose(rng)
+                .unwrap()
+                .transformed(transform);
+            let room_min = vec2(
+                building.iter().map(|(p, _)| p.x).min().unwrap(),
+                building.iter().map(|(p, _)| p.y).min().unwrap(),
+            );
+            let room_max = vec2(
+                building.iter().map(|(p, _)| p.x).max().unwrap(),
+                building.iter().map(|(p, _)| p.y).max().unwrap(),
+            );
+            let slack = vec2(LOT_SIZE, LOT_SIZE) - (room_max - room_min) - vec2(1, 1);
+            if slack.x < 0 || slack.y < 0 {
+                continue;
+            }
+
+            let offset = lot_min - room_min
+                + vec2(rng.gen_range(0, slack.x + 1), rng.gen_range(0, slack.y + 1));
+            if map.is_valid_placement(offset, &building) {
+                map.place_room_at(offset, &building);
+            }
+        }
+
+        self.scatter_spawns(rng, &mut map);
+
+        map
+    }
+
+    /// Scatter random spawns over the open ground of an overland map.
+    ///
+    /// Closed pockets the player can't get to, like islands in a lake or walled-in lots, are left
+    /// empty.
+    fn scatter_spawns(&self, rng: &mut Rng, map: &mut Map) {
+        let stranded = map.stranded_positions();
+        for pos in map.open_ground() {
+            if stranded.contains(&pos) || !map[pos].spawns.is_empty() {
+                continue;
+            }
             if let Some(spawn) = self.sample(rng) {
                 map.push_spawn(pos, spawn);
             }
         }
+    }
 
-        map
+    /// Sample a random entity that can spawn in the sector.
+    ///
+    /// Returns `None` if nothing can live here, eg. in water sectors.
+    fn sample_spawn(&self, rng: &mut Rng) -> Option<EntitySpawn> {
+        let item = spec::iter_specs().weighted_choice(rng, |item| {
+            if item.rarity() == 0.0 || !self.can_spawn(&**item, self.depth) {
+                0.0
+            } else {
+                1.0 / item.rarity()
+            }
+        })?;
+        Some(EntitySpawn::from_str(item.id()).unwrap())
     }
 
-    fn can_spawn(&self, spec: &dyn Spec) -> bool {
-        spec.min_depth() <= self.depth && (spec.habitat() & (1 << self.biome as u64)) != 0
+    /// Return whether the spec can live in the sector's biome at the given depth.
+    fn can_spawn(&self, spec: &dyn Spec, depth: i32) -> bool {
+        spec.min_depth() <= depth && (spec.habitat() & (1 << self.biome as u64)) != 0
     }
-}
 
-impl Distribution<EntitySpawn> for ConnectedSectorSpec<'_> {
-    fn sample(&self, rng: &mut Rng) -> EntitySpawn {
-        let item = spec::iter_specs()
-            .weighted_choice(rng, |item| {
-                if item.rarity() == 0.0 || !self.can_spawn(&**item) {
-                    0.0
-                } else {
-                    1.0 / item.rarity()
+    /// Fill the spawn slots left by vaults and add random encounters to the map.
+    fn populate(&self, rng: &mut Rng, map: &mut Map) {
+        const ENCOUNTER_ONE_IN: u32 = 3;
+        const TOUGH_MONSTER_DEPTH_BONUS: i32 = 3;
+
+        for (pos, slot) in map.take_spawn_slots() {
+            match slot {
+                SpawnSlot::Monster => {
+                    if let Some(spawn) = self.sample_monster(rng, self.depth) {
+                        map.push_spawn(pos, spawn);
+                    }
                 }
-            })
-            .unwrap();
+                SpawnSlot::ToughMonster => {
+                    let depth = self.depth + TOUGH_MONSTER_DEPTH_BONUS;
+                    if let Some(spawn) = self.sample_monster(rng, depth) {
+                        map.push_spawn(pos, spawn);
+                    }
+                }
+                SpawnSlot::Encounter => {
+                    if let Some(encounter) = self.sample_encounter(rng) {
+                        place_group(map, pos, encounter.sample(rng));
+                    }
+                }
+                SpawnSlot::Boss => {
+                    if let Some(spawn) = spec::boss_at(self.depth) {
+                        map.push_spawn(pos, spawn);
+                    }
+                }
+            }
+        }
+
+        if self.holds_artifact() {
+            let sites = map.find_positions(|_, c| c.spawns.is_empty() && c.is_walkable());
+            if let Some(&pos) = sites.choose(rng) {
+                map.push_spawn(pos, EntitySpawn::from_str(spec::ARTIFACT).unwrap());
+            }
+        }
+
+        if self.depth == -1 || !rng.one_chance_in(ENCOUNTER_ONE_IN) {
+            return;
+        }
+        let stranded = map.stranded_positions();
+        let sites = map.find_positions(|p, c| {
+            c.spawns.is_empty() && c.is_walkable() && !c.is_border() && !stranded.contains(&p)
+        });
+        if let (Some(&center), Some(encounter)) = (sites.choose(rng), self.sample_encounter(rng)) {
+            place_group(map, center, encounter.sample(rng));
+        }
+    }
+
+    /// Return whether this is the bottom level of the dungeon below the starting sector, where the
+    /// artifact the player must retrieve to win lies.
+    fn holds_artifact(&self) -> bool {
+        self.sector.x == 0
+            && self.sector.y == 0
+            && self.sector.z < 0
+            && self.neighbor(SectorDir::Down).is_none()
+    }
+
+    /// Sample a random monster that can live in the sector at the given depth.
+    fn sample_monster(&self, rng: &mut Rng, depth: i32) -> Option<EntitySpawn> {
+        let spec = spec::iter_specs().weighted_choice(rng, |spec| {
+            if !spec.is_mob() || spec.rarity() == 0.0 || !self.can_spawn(&**spec, depth) {
+                0.0
+            } else {
+                1.0 / spec.rarity()
+            }
+        })?;
+        Some(EntitySpawn::from_str(spec.id()).unwrap())
+    }
 
-        EntitySpawn::from_str(item.id()).unwrap()
+    fn sample_encounter(&self, rng: &mut Rng) -> Option<&'static EncounterSpec> {
+        spec::ENCOUNTERS.iter().weighted_choice(rng, |e| {
+            if e.rarity() == 0.0
+                || e.min_depth() > self.depth
+                || (e.habitat() & (1 << self.biome as u64)) == 0
+            {
+                0.0
+            } else {
+                1.0 / e.rarity()
+            }
+        })
+    }
+}
+
+/// Place a group of spawns on the map, starting from the center and spreading out from there.
+///
+/// Spawns only go to walkable cells that can be reached from the center without going through
+/// walls, so the group won't be split between separate rooms.
+fn place_group(map: &mut Map, center: CellVector, spawns: Vec<EntitySpawn>) {
+    let mut spawns = spawns.into_iter();
+    let mut open = VecDeque::new();
+    let mut seen = HashSet::new();
+    open.push_back(center);
+    seen.insert(center);
+
+    while let Some(pos) = open.pop_front() {
+        if map[pos].spawns.is_empty() && !map[pos].is_border() {
+            match spawns.next() {
+                Some(spawn) => map.push_spawn(pos, spawn),
+                None => return,
+            }
+        }
+
+        for p in calx::hex_neighbors(pos) {
+            if !seen.contains(&p) && map.get(p).map_or(false, |c| c.is_walkable()) {
+                seen.insert(p);
+                open.push_back(p);
+            }
+        }
     }
 }
 
@@ -802,11 +1515,12 @@ impl Distribution<Option<EntitySpawn>> for ConnectedSectorSpec<'_> {
 
         let spawn_one_in = match self.biome {
             Dungeon => 10,
+            City => 40,
             _ => 100,
         };
 
         if rng.one_chance_in(spawn_one_in) {
-            Some(self.sample(rng))
+            self.sample_spawn(rng)
         } else {
             None
         }
@@ -867,6 +1581,180 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_dungeon_style() {
+        use super::{DungeonStyle, SectorSpec};
+
+        let mut spec = SectorSpec::default();
+        assert_eq!(spec.dungeon_style(), DungeonStyle::Rooms);
+        spec.depth = 5;
+        assert_eq!(spec.dungeon_style(), DungeonStyle::Caves);
+        spec.dungeon_style = Some(DungeonStyle::Rooms);
+        assert_eq!(spec.dungeon_style(), DungeonStyle::Rooms);
+    }
+
+    #[test]
+    fn test_generate_builtin_worlds() {
+        use super::{generate, WorldSkeleton, BUILTIN_WORLDS};
+
+        for (name, _) in BUILTIN_WORLDS {
+            let skeleton = WorldSkeleton::builtin(name).unwrap();
+            for &sector in skeleton.keys() {
+                let map = generate(1, sector, &skeleton);
+                let stranded = map.stranded_positions();
+                for (p, c) in map.iter() {
+                    if c.spawns.is_empty() {
+                        continue;
+                    }
+                    assert!(
+                        c.is_walkable(),
+                        "{} {:?}: spawns on {:?} at {:?}",
+                        name,
+                        sector,
+                        c.terrain,
+                        p
+                    );
+                    assert!(
+                        !stranded.contains(p),
+                        "{} {:?}: stranded spawns at {:?}",
+                        name,
+                        sector,
+                        p
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_connection() {
+        use super::{ConnectedSectorSpec, Connection, SectorDir, SectorSpec, WorldSkeleton};
+
+        let mut skeleton = WorldSkeleton::default();
+        let mut spec = SectorSpec::default();
+        spec.connections.insert(SectorDir::East, Connection::Door);
+        skeleton.insert(Sector::new(0, 0, 0), spec);
+        skeleton.insert(Sector::new(1, 0, 0), SectorSpec::default());
+        let mut spec = SectorSpec::default();
+        spec.connections.insert(SectorDir::Up, Connection::Blocked);
+        skeleton.insert(Sector::new(0, 0, -1), spec);
+
+        let west = ConnectedSectorSpec::new(1, Sector::new(0, 0, 0), &skeleton);
+        let east = ConnectedSectorSpec::new(1, Sector::new(1, 0, 0), &skeleton);
+        assert_eq!(west.connection(SectorDir::East), Connection::Door);
+        assert_eq!(east.connection(SectorDir::West), Connection::Door);
+        assert_eq!(west.connection(SectorDir::West), Connection::Blocked);
+        assert_eq!(west.connection(SectorDir::Down), Connection::Blocked);
+    }
+
+    #[test]
+    fn test_locked_connection() {
+        use super::{generate, Biome, Connection, SectorDir, SectorSpec, WorldSkeleton};
+        use crate::{map::MapCell, spec::EntitySpawn, Terrain};
+        use std::str::FromStr;
+
+        let mut skeleton = WorldSkeleton::default();
+        let mut spec = SectorSpec {
+            biome: Biome::Grassland,
+            ..Default::default()
+        };
+        skeleton.insert(Sector::new(1, 0, 0), spec.clone());
+        spec.connections
+            .insert(SectorDir::East, Connection::Locked("silver key".into()));
+        skeleton.insert(Sector::new(0, 0, 0), spec);
+
+        let key = EntitySpawn::from_str("silver key").unwrap();
+        let count = |sector, p: &dyn Fn(&MapCell) -> bool| {
+            generate(1, sector, &skeleton)
+                .iter()
+                .filter(|(_, c)| p(c))
+                .count()
+        };
+        for &sector in &[Sector::new(0, 0, 0), Sector::new(1, 0, 0)] {
+            assert_eq!(count(sector, &|c| c.terrain == Terrain::LockedDoor), 1);
+        }
+        // The key is outside the sector that asked for the lock.
+        assert_eq!(count(Sector::new(0, 0, 0), &|c| c.spawns.contains(&key)), 0);
+        assert_eq!(count(Sector::new(1, 0, 0), &|c| c.spawns.contains(&key)), 1);
+    }
+
+    #[test]
+    fn test_world_definitions() {
+        use super::{Biome, WorldSkeleton, BUILTIN_WORLDS};
+
+        for (name, _) in BUILTIN_WORLDS {
+            let skeleton = WorldSkeleton::builtin(name).unwrap();
+            assert!(skeleton.contains_key(&Sector::new(0, 0, 0)));
+        }
+
+        let skeleton: WorldSkeleton = "(
+            overworld: \"^[.]\",
+            branches: [(entrance: (-1, 0), levels: 2)],
+        )"
+        .parse()
+        .unwrap();
+        assert_eq!(skeleton.len(), 4);
+        assert_eq!(skeleton[&Sector::new(-1, 0, -2)].biome, Biome::Dungeon);
+        assert_eq!(skeleton[&Sector::new(-1, 0, -2)].depth, 2);
+
+        // The dive keeps the old numbering, a populated top level at depth 0 and nine below it.
+        let skeleton = WorldSkeleton::builtin("dive").unwrap();
+        for z in 0..10 {
+            assert_eq!(skeleton[&Sector::new(0, 0, -z)].depth, z as i32);
+        }
+
+        assert!(
+            "(overworld: \"[.]\", branches: [(entrance: (5, 5), levels: 1)])"
+                .parse::<WorldSkeleton>()
+                .is_err()
+        );
+        assert!("(overworld: \"[!]\")".parse::<WorldSkeleton>().is_err());
+    }
+
+    #[test]
+    fn test_boss_placement() {
+        use super::{generate, WorldSkeleton};
+        use crate::spec::{boss_at, iter_specs};
+
+        let skeleton = WorldSkeleton::builtin("dive").unwrap();
+        for spec in iter_specs().filter(|s| s.is_unique()) {
+            let boss = boss_at(spec.min_depth()).unwrap();
+            let (&sector, _) = skeleton
+                .iter()
+                .find(|(_, s)| s.depth == spec.min_depth())
+                .unwrap();
+            for seed in 0..4 {
+                assert!(
+                    generate(seed, sector, &skeleton)
+                        .iter()
+                        .any(|(_, c)| c.spawns.contains(&boss)),
+                    "{} not placed with seed {}",
+                    boss,
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_artifact_placement() {
+        use super::{generate, WorldSkeleton};
+        use crate::spec::{EntitySpawn, ARTIFACT};
+        use std::str::FromStr;
+
+        let skeleton = WorldSkeleton::builtin("tiny").unwrap();
+        let artifact = EntitySpawn::from_str(ARTIFACT).unwrap();
+        let count = |z| {
+            generate(1, Sector::new(0, 0, z), &skeleton)
+                .iter()
+                .filter(|(_, c)| c.spawns.contains(&artifact))
+                .count()
+        };
+        // Only the bottom level of the starting branch has the artifact.
+        assert_eq!(count(-2), 1);
+        assert_eq!(count(-1), 0);
+    }
+
     #[test]
     fn test_stair_locations() {
         for z in -10..10 {
@@ -4,13 +4,15 @@ use crate::{
     ai::{Brain, ShoutType},
     desc::{Desc, Icon},
     item::ItemType,
-    item::{Item, Stacking},
+    item::{Item, Remains, Stacking},
     sector::Biome,
     stats::{Health, Intrinsic, Stats, StatsComponent, Statuses},
     world::Loadout,
-    Anim, Distribution, ExternalEntity, Rng,
+    Anim, Distribution, ExternalEntity, Rng, Slot,
 };
+use calx::{RngExt, WeightedChoice};
 use lazy_static::lazy_static;
+use rand::Rng as _;
 use serde;
 use std::collections::BTreeMap;
 use std::error::Error;
@@ -35,6 +37,17 @@ pub trait Spec: Distribution<ExternalEntity> + Sync + Send {
 
     fn name(&self) -> &str;
 
+    /// Is this a creature instead of an item?
+    fn is_mob(&self) -> bool { false }
+
+    /// Is this a named unique that can only exist once per world?
+    fn is_unique(&self) -> bool { false }
+
+    /// Generate an entity with random variation suitable for the given depth.
+    ///
+    /// Plain sampling gives the basic version of the entity.
+    fn generate(&self, rng: &mut Rng, _depth: i32) -> ExternalEntity { self.sample(rng) }
+
     /// Return base id of entity without pluralization
     fn id(&self) -> &str {
         let name = self.name();
@@ -62,6 +75,21 @@ pub struct MobSpec {
     power: i32,
     intrinsics: Vec<Intrinsic>,
     shout: ShoutType,
+    /// Spawn names of items the mob starts with.
+    ///
+    /// Equipment goes into free equipment slots, everything else into the bag.
+    gear: Vec<&'static str>,
+    /// Spawn names of items the mob may drop on death and the one-in-n chance for each.
+    ///
+    /// Use 1 for guaranteed drops. Loot is rolled when the mob is created and carried in its bag.
+    loot: Vec<(&'static str, u32)>,
+    /// Whether the mob leaves a corpse when it dies.
+    corpse: bool,
+    /// Unique mobs have a proper name and only spawn once per world.
+    ///
+    /// They never spawn randomly, but show up in the boss vault of the dungeon level at their
+    /// minimum depth.
+    unique: bool,
 }
 
 impl Default for MobSpec {
@@ -75,23 +103,78 @@ impl Default for MobSpec {
             power: 0,
             intrinsics: Vec::new(),
             shout: ShoutType::Silent,
+            gear: Vec::new(),
+            loot: Vec::new(),
+            corpse: true,
+            unique: false,
         }
     }
 }
 
-impl Distribution<ExternalEntity> for MobSpec {
-    fn sample(&self, _: &mut Rng) -> ExternalEntity {
-        ExternalEntity::new(
+impl MobSpec {
+    fn build(
+        &self,
+        rng: &mut Rng,
+        power: i32,
+        desc: Desc,
+        make_item: impl Fn(&EntitySpawn, &mut Rng) -> ExternalEntity,
+    ) -> ExternalEntity {
+        let mut ret = ExternalEntity::new(
             Loadout::default()
-                .c(StatsComponent::new(Stats::new(
-                    self.power,
-                    &self.intrinsics,
-                )))
-                .c(Desc::new(&self.name, self.icon))
+                .c(StatsComponent::new(Stats::new(power, &self.intrinsics)))
+                .c(desc)
                 .c(Brain::enemy().shout(self.shout))
                 .c(Anim::default())
                 .c(Health::default())
-                .c(Statuses::default()),
+                .c(Statuses::default())
+                .c(Remains {
+                    corpse: self.corpse,
+                }),
+        );
+
+        let mut bag_idx = 0;
+        for name in &self.gear {
+            let item = make_item(&self.item_spawn(name), rng);
+            let equip_type = item
+                .loadout
+                .item
+                .as_ref()
+                .and_then(|i| i.item_type.equip_type());
+            let slot = Slot::equipment_iter()
+                .cloned()
+                .find(|slot| slot.accepts(equip_type) && !ret.contents.contains_key(slot))
+                .unwrap_or_else(|| {
+                    bag_idx += 1;
+                    Slot::Bag(bag_idx - 1)
+                });
+            ret.contents.insert(slot, item);
+        }
+
+        for &(name, one_in) in &self.loot {
+            let spawn = self.item_spawn(name);
+            if rng.one_chance_in(one_in) {
+                ret.contents
+                    .insert(Slot::Bag(bag_idx), make_item(&spawn, rng));
+                bag_idx += 1;
+            }
+        }
+
+        ret
+    }
+
+    fn item_spawn(&self, name: &str) -> EntitySpawn {
+        EntitySpawn::from_str(name)
+            .unwrap_or_else(|_| panic!("Mob {} has unknown item {}", self.name, name))
+    }
+}
+
+impl Distribution<ExternalEntity> for MobSpec {
+    fn sample(&self, rng: &mut Rng) -> ExternalEntity {
+        self.build(
+            rng,
+            self.power,
+            Desc::new(&self.name, self.icon),
+            |spawn, rng| spawn.sample(rng),
         )
     }
 }
@@ -101,6 +184,31 @@ impl Spec for MobSpec {
     fn min_depth(&self) -> i32 { self.depth }
     fn habitat(&self) -> u64 { self.habitat }
     fn name(&self) -> &str { &self.name }
+    fn is_mob(&self) -> bool { true }
+    fn is_unique(&self) -> bool { self.unique }
+
+    fn generate(&self, rng: &mut Rng, depth: i32) -> ExternalEntity {
+        // Uniques are always exactly as designed.
+        if self.unique {
+            return self.sample(rng);
+        }
+
+        /// How much deeper than its minimum depth a mob must be found to be a possible elite.
+        const ELITE_DEPTH: i32 = 3;
+        const ELITE_ONE_IN: u32 = 4;
+
+        // Individual mobs are a bit stronger or weaker than the baseline.
+        let variance = self.power / 5;
+        let mut power = self.power + rng.gen_range(-variance, variance + 1);
+        let mut desc = Desc::new(&self.name, self.icon);
+
+        if depth >= self.depth + ELITE_DEPTH && rng.one_chance_in(ELITE_ONE_IN) {
+            power = power * 3 / 2 + 1;
+            desc = desc.suffix("veteran");
+        }
+
+        self.build(rng, power, desc, |spawn, rng| spawn.generate(rng, depth))
+    }
 }
 
 #[derive(Debug)]
@@ -139,24 +247,33 @@ impl Default for ItemSpec {
     }
 }
 
-impl Distribution<ExternalEntity> for ItemSpec {
-    fn sample(&self, _: &mut Rng) -> ExternalEntity {
+impl ItemSpec {
+    fn loadout(&self, stats: Stats, desc: Desc, charges: u32) -> Loadout {
         let mut loadout = Loadout::default()
-            .c(Desc::new(&self.name, self.icon))
-            .c(StatsComponent::new(
-                Stats::new(self.power, &self.intrinsics)
-                    .armor(self.armor)
-                    .attack(self.attack)
-                    .defense(self.defense),
-            ))
+            .c(desc)
+            .c(StatsComponent::new(stats))
             .c(Item {
                 item_type: self.item_type,
-                charges: 1,
+                charges,
+                discarded: false,
             });
         if self.stacks {
             loadout = loadout.c(Stacking::default());
         }
-        ExternalEntity::new(loadout)
+        loadout
+    }
+
+    fn base_stats(&self) -> Stats {
+        Stats::new(self.power, &self.intrinsics)
+            .armor(self.armor)
+            .attack(self.attack)
+            .defense(self.defense)
+    }
+}
+
+impl Distribution<ExternalEntity> for ItemSpec {
+    fn sample(&self, _: &mut Rng) -> ExternalEntity {
+        ExternalEntity::new(self.loadout(self.base_stats(), Desc::new(&self.name, self.icon), 1))
     }
 }
 
@@ -165,8 +282,138 @@ impl Spec for ItemSpec {
     fn min_depth(&self) -> i32 { self.depth }
     fn habitat(&self) -> u64 { self.habitat }
     fn name(&self) -> &str { &self.name }
+
+    fn generate(&self, rng: &mut Rng, depth: i32) -> ExternalEntity {
+        const ENCHANT_ONE_IN: u32 = 3;
+        const CURSE_ONE_IN: u32 = 5;
+        const AFFIX_ONE_IN: u32 = 6;
+
+        let mut stats = self.base_stats();
+        let mut desc = Desc::new(&self.name, self.icon);
+        let depth = depth.max(0);
+
+        if rng.one_chance_in(ENCHANT_ONE_IN) {
+            let mut bonus = 1 + rng.gen_range(0, 1 + depth / 3);
+            if rng.one_chance_in(CURSE_ONE_IN) {
+                bonus = -bonus;
+            }
+
+            let enchanted = match self.item_type {
+                ItemType::MeleeWeapon | ItemType::RangedWeapon => {
+                    stats.base_attack += bonus;
+                    true
+                }
+                ItemType::Helmet | ItemType::Armor | ItemType::Boots => {
+                    stats.armor += bonus;
+                    true
+                }
+                ItemType::Trinket => {
+                    stats.base_defense += bonus;
+                    true
+                }
+                _ => false,
+            };
+            if enchanted {
+                desc = desc.prefix(format!("{:+}", bonus));
+            }
+        }
+
+        if EQUIPMENT.contains(&self.item_type) && rng.one_chance_in(AFFIX_ONE_IN) {
+            let affix = AFFIXES.iter().weighted_choice(rng, |a| {
+                if a.depth > depth || !a.item_types.contains(&self.item_type) {
+                    0.0
+                } else {
+                    1.0 / a.rarity
+                }
+            });
+            if let Some(affix) = affix {
+                stats = stats + affix.stats();
+                desc = desc.suffix(affix.name);
+            }
+        }
+
+        let charges = match self.item_type {
+            ItemType::TargetedUsable(_) => rng.gen_range(3, 6) + depth as u32 / 4,
+            _ => 1,
+        };
+
+        ExternalEntity::new(self.loadout(stats, desc, charges))
+    }
 }
 
+const EQUIPMENT: &[ItemType] = &[
+    ItemType::MeleeWeapon,
+    ItemType::RangedWeapon,
+    ItemType::Helmet,
+    ItemType::Armor,
+    ItemType::Boots,
+    ItemType::Trinket,
+];
+
+/// Magic property that can show up on generated equipment.
+struct Affix {
+    name: &'static str,
+    depth: i32,
+    rarity: f32,
+    item_types: &'static [ItemType],
+    attack: i32,
+    defense: i32,
+    armor: i32,
+    intrinsics: &'static [Intrinsic],
+}
+
+impl Affix {
+    fn stats(&self) -> Stats {
+        Stats::new(0, self.intrinsics)
+            .attack(self.attack)
+            .defense(self.defense)
+            .armor(self.armor)
+    }
+}
+
+const AFFIXES: &[Affix] = &[
+    Affix {
+        name: "of speed",
+        depth: 4,
+        rarity: 4.0,
+        item_types: &[ItemType::Boots, ItemType::Trinket],
+        attack: 0,
+        defense: 0,
+        armor: 0,
+        intrinsics: &[Intrinsic::Quick],
+    },
+    Affix {
+        name: "of accuracy",
+        depth: 0,
+        rarity: 1.0,
+        item_types: &[ItemType::MeleeWeapon, ItemType::RangedWeapon],
+        attack: 3,
+        defense: 0,
+        armor: 0,
+        intrinsics: &[],
+    },
+    Affix {
+        name: "of protection",
+        depth: 2,
+        rarity: 2.0,
+        item_types: EQUIPMENT,
+        attack: 0,
+        defense: 3,
+        armor: 0,
+        intrinsics: &[],
+    },
+    Affix {
+        name: "of fortitude",
+        depth: 3,
+        rarity: 2.0,
+        item_types: &[ItemType::Helmet, ItemType::Armor],
+        attack: 0,
+        defense: 0,
+        armor: 2,
+        intrinsics: &[],
+    },
+];
+
 macro_rules! specs {
     {$($item:expr,)+}
     =>
@@ -181,8 +428,19 @@ macro_rules! specs {
     }
 }
 
+/// Spawn name of the artifact the player must bring back to the surface to win the game.
+pub const ARTIFACT: &str = "crown of Magog";
+
 pub fn iter_specs() -> impl Iterator<Item = Arc<dyn Spec + 'static>> { SPECS.values().cloned() }
 
+/// Return the unique monster that guards the given dungeon depth.
+pub fn boss_at(depth: i32) -> Option<EntitySpawn> {
+    SPECS
+        .iter()
+        .find(|(_, spec)| spec.is_unique() && spec.min_depth() == depth)
+        .map(|(spawn, _)| spawn.clone())
+}
+
 use self::Intrinsic::*;
 use self::ShoutType::*;
 use crate::effect::Ability::*;
@@ -203,10 +461,12 @@ specs! {
     MobSpec {
         name: "dreg".into(),
         icon: I::Dreg,
-        habitat: DUNGEON,
+        habitat: DUNGEON | URBAN,
         power: 2,
         intrinsics: vec![Hands],
         shout: Shout,
+        gear: vec!["club"],
+        loot: vec![("scroll of lightning", 10)],
         ..d()
     },
     MobSpec {
@@ -221,9 +481,10 @@ specs! {
         name: "ooze".into(),
         icon: I::Ooze,
         depth: 1,
-        habitat: DUNGEON,
+        habitat: DUNGEON | URBAN,
         power: 3,
         shout: Gurgle,
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -252,6 +513,8 @@ specs! {
         power: 7,
         intrinsics: vec![Hands],
         shout: Shout,
+        gear: vec!["club"],
+        loot: vec![("wand of fireball", 4)],
         ..d()
     },
     MobSpec {
@@ -262,6 +525,7 @@ specs! {
         rarity: 6.0,
         power: 10,
         intrinsics: vec![Hands],
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -272,6 +536,7 @@ specs! {
         rarity: 8.0,
         power: 14,
         intrinsics: vec![Hands],
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -311,6 +576,7 @@ specs! {
         rarity: 5.0,
         power: 5,
         intrinsics: vec![Hands],
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -330,6 +596,7 @@ specs! {
         rarity: 3.0,
         shout: Shout,
         intrinsics: vec![Hands],
+        gear: vec!["bow"],
         ..d()
     },
     MobSpec {
@@ -340,6 +607,7 @@ specs! {
         power: 8,
         rarity: 5.0,
         intrinsics: vec![Hands],
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -350,6 +618,7 @@ specs! {
         power: 8,
         rarity: 5.0,
         shout: Shout,
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -360,6 +629,7 @@ specs! {
         power: 5,
         rarity: 3.0,
         shout: Gurgle,
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -379,6 +649,7 @@ specs! {
         depth: 5,
         power: 5,
         rarity: 10.0,
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -388,6 +659,7 @@ specs! {
         depth: 8,
         power: 10,
         rarity: 10.0,
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -409,6 +681,29 @@ specs! {
         intrinsics: vec![Hands],
         ..d()
     },
+    MobSpec {
+        name: "lizardman chief".into(),
+        icon: I::Lizardman,
+        habitat: TEMPERATE | ARID,
+        depth: 3,
+        power: 6,
+        rarity: 0.0,
+        shout: Shout,
+        intrinsics: vec![Hands],
+        gear: vec!["sword", "helmet"],
+        loot: vec![("scroll of lightning", 1)],
+        ..d()
+    },
+    MobSpec {
+        name: "spiderling".into(),
+        icon: I::Bug,
+        habitat: DUNGEON | ARID,
+        depth: 4,
+        power: 2,
+        rarity: 0.0,
+        shout: Hiss,
+        ..d()
+    },
     MobSpec {
         name: "centipede".into(),
         icon: I::Centipede,
@@ -433,6 +728,7 @@ specs! {
         power: 8,
         rarity: 3.0,
         intrinsics: vec![Deathsplosion],
+        corpse: false,
         ..d()
     },
     MobSpec {
@@ -459,8 +755,78 @@ specs! {
         ..d()
     },
 
+    // Uniques
+    MobSpec {
+        name: "Grelk".into(),
+        icon: I::Dreg,
+        habitat: DUNGEON,
+        depth: 3,
+        power: 8,
+        rarity: 0.0,
+        shout: Shout,
+        intrinsics: vec![Hands, Quick],
+        gear: vec!["club", "helmet"],
+        loot: vec![("wand of confusion", 1)],
+        unique: true,
+        ..d()
+    },
+    MobSpec {
+        name: "Ugrakh".into(),
+        icon: I::Ogre,
+        habitat: DUNGEON,
+        depth: 5,
+        power: 16,
+        rarity: 0.0,
+        shout: Roar,
+        intrinsics: vec![Hands],
+        gear: vec!["sword", "armor"],
+        loot: vec![("wand of fireball", 1)],
+        unique: true,
+        ..d()
+    },
+    MobSpec {
+        name: "Xiloth".into(),
+        icon: I::EyeHorror,
+        habitat: DUNGEON,
+        depth: 8,
+        power: 24,
+        rarity: 0.0,
+        intrinsics: vec![Deathsplosion, Hyperactive],
+        loot: vec![("scroll of lightning", 1)],
+        corpse: false,
+        unique: true,
+        ..d()
+    },
+
 
     // Items
+    ItemSpec {
+        name: "club".into(),
+        icon: I::Sword,
+        item_type: MeleeWeapon,
+        rarity: 5.0,
+        attack: 3,
+        ..d()
+    },
+    // TODO: Ranged attacks, bows only give an attack bonus for now.
+    ItemSpec {
+        name: "bow".into(),
+        icon: I::Sword,
+        item_type: RangedWeapon,
+        rarity: 10.0,
+        depth: 2,
+        attack: 2,
+        ..d()
+    },
+    ItemSpec {
+        name: "crown of Magog|crowns of Magog".into(),
+        icon: I::Crown,
+        item_type: Helmet,
+        rarity: 0.0,
+        armor: 2,
+        defense: 2,
+        ..d()
+    },
     ItemSpec {
         name: "sword".into(),
         icon: I::Sword,
@@ -511,6 +877,112 @@ specs! {
         stacks: true,
         ..d()
     },
+    // Keys don't show up at random, sector generation places them for locked connections.
+    ItemSpec {
+        name: "silver key".into(),
+        icon: I::Key,
+        item_type: Key,
+        rarity: 0.0,
+        ..d()
+    },
+    ItemSpec {
+        name: "gold key".into(),
+        icon: I::Key,
+        item_type: Key,
+        rarity: 0.0,
+        ..d()
+    },
+}
+
+/// A group of monsters that spawn together.
+#[derive(Debug)]
+pub struct EncounterSpec {
+    name: String,
+    depth: i32,
+    rarity: f32,
+    habitat: u64,
+    /// Spawn names with the minimum and maximum number of each.
+    ///
+    /// The first spawn is placed at the center of the encounter and the rest around it.
+    members: Vec<(&'static str, u32, u32)>,
+}
+
+impl Default for EncounterSpec {
+    fn default() -> Self {
+        EncounterSpec {
+            name: "N/A".into(),
+            depth: 0,
+            rarity: 1.0,
+            habitat: EVERYWHERE,
+            members: Vec::new(),
+        }
+    }
+}
+
+impl EncounterSpec {
+    pub fn rarity(&self) -> f32 { self.rarity }
+    pub fn min_depth(&self) -> i32 { self.depth }
+    pub fn habitat(&self) -> u64 { self.habitat }
+}
+
+impl Distribution<Vec<EntitySpawn>> for EncounterSpec {
+    fn sample(&self, rng: &mut Rng) -> Vec<EntitySpawn> {
+        let mut ret = Vec::new();
+        for &(name, min, max) in &self.members {
+            let spawn = EntitySpawn::from_str(name)
+                .unwrap_or_else(|_| panic!("Encounter {} has unknown spawn {}", self.name, name));
+            for _ in 0..rng.gen_range(min, max + 1) {
+                ret.push(spawn.clone());
+            }
+        }
+        ret
+    }
+}
+
+lazy_static! {
+    pub static ref ENCOUNTERS: Vec<EncounterSpec> = vec![
+        EncounterSpec {
+            name: "rat pack".into(),
+            habitat: DUNGEON | TEMPERATE | ARID,
+            rarity: 2.0,
+            members: vec![("rat", 3, 6)],
+            ..d()
+        },
+        EncounterSpec {
+            name: "dreg gang".into(),
+            habitat: DUNGEON | URBAN,
+            rarity: 2.0,
+            members: vec![("dreg", 2, 4)],
+            ..d()
+        },
+        EncounterSpec {
+            name: "lizardman war party".into(),
+            habitat: TEMPERATE | ARID,
+            depth: 3,
+            rarity: 3.0,
+            members: vec![("lizardman chief", 1, 1), ("lizardman", 2, 4)],
+        },
+        EncounterSpec {
+            name: "spider nest".into(),
+            habitat: DUNGEON | ARID,
+            depth: 4,
+            rarity: 5.0,
+            members: vec![("spider", 1, 1), ("spiderling", 3, 5)],
+        },
+    ];
+}
+
+/// Placeholder for spawns in prefab maps, filled in when the map is placed in a sector.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SpawnSlot {
+    /// A monster suitable for the depth and biome of the sector.
+    Monster,
+    /// A monster from further down than the sector.
+    ToughMonster,
+    /// A group of monsters from the encounter tables.
+    Encounter,
+    /// The unique monster guarding the sector's depth, if there is one.
+    Boss,
 }
 
 /// String that's guaranteed to describe an entity spawn.
@@ -561,13 +1033,23 @@ impl<'a> serde::Deserialize<'a> for EntitySpawn {
     }
 }
 
-impl Distribution<ExternalEntity> for EntitySpawn {
-    fn sample(&self, rng: &mut Rng) -> ExternalEntity {
+impl EntitySpawn {
+    fn spec(&self) -> &Arc<dyn Spec> {
         SPECS
             .get(self)
             .unwrap_or_else(|| panic!("EntitySpawn {:?} not found in spec database", self))
-            .sample(rng)
     }
+
+    /// Generate the entity with random variation suitable for the given depth.
+    pub fn generate(&self, rng: &mut Rng, depth: i32) -> ExternalEntity {
+        self.spec().generate(rng, depth)
+    }
+
+    pub fn is_unique(&self) -> bool { self.spec().is_unique() }
+}
+
+impl Distribution<ExternalEntity> for EntitySpawn {
+    fn sample(&self, rng: &mut Rng) -> ExternalEntity { self.spec().sample(rng) }
 }
 
 // Helpers for data conciseness.
@@ -576,6 +1058,89 @@ fn d<T: Default>() -> T { Default::default() }
 
 #[cfg(test)]
 mod test {
+    #[test]
+    fn test_item_generation() {
+        use super::EntitySpawn;
+        use std::str::FromStr;
+
+        let sword = EntitySpawn::from_str("sword").unwrap();
+        let mut rng = calx::seeded_rng(&1);
+        let swords: Vec<_> = (0..100).map(|_| sword.generate(&mut rng, 10)).collect();
+        assert!(swords
+            .iter()
+            .any(|s| s.loadout.desc.as_ref().unwrap().prefix.is_some()));
+        assert!(swords
+            .iter()
+            .any(|s| s.loadout.desc.as_ref().unwrap().prefix.is_none()));
+
+        // Same seed gives the same item.
+        assert_eq!(
+            sword.generate(&mut calx::seeded_rng(&2), 10),
+            sword.generate(&mut calx::seeded_rng(&2), 10)
+        );
+    }
+
+    #[test]
+    fn test_mob_gear() {
+        use super::EntitySpawn;
+        use crate::Slot;
+        use std::str::FromStr;
+
+        let dreg = EntitySpawn::from_str("dreg").unwrap();
+        let dreg = dreg.generate(&mut calx::seeded_rng(&1), 0);
+        assert!(dreg.contents.contains_key(&Slot::RightHand));
+
+        // Elites show up deep enough and keep the base name in front.
+        let dreg = EntitySpawn::from_str("dreg").unwrap();
+        let mut rng = calx::seeded_rng(&1);
+        assert!((0..100).any(|_| {
+            dreg.generate(&mut rng, 10)
+                .loadout
+                .desc
+                .unwrap()
+                .full_name(false)
+                == "dreg veteran"
+        }));
+
+        // Guaranteed loot is always carried.
+        let chief = EntitySpawn::from_str("lizardman chief").unwrap();
+        let chief = chief.generate(&mut calx::seeded_rng(&1), 3);
+        assert!(chief.contents.contains_key(&Slot::Bag(0)));
+
+        // Every piece of gear and loot must exist and fit in an equipment slot or the bag.
+        let mut rng = calx::seeded_rng(&1);
+        for spawn in super::SPECS.keys() {
+            let _ = spawn.generate(&mut rng, 10);
+        }
+    }
+
+    #[test]
+    fn test_uniques() {
+        use super::{boss_at, SPECS};
+
+        let uniques: Vec<_> = SPECS.iter().filter(|(_, s)| s.is_unique()).collect();
+        assert!(!uniques.is_empty());
+        for (spawn, spec) in uniques {
+            // Uniques must never show up from random sampling and need a proper name.
+            assert_eq!(spec.rarity(), 0.0);
+            assert!(spec.name().chars().next().unwrap().is_uppercase());
+            assert_eq!(boss_at(spec.min_depth()).as_ref(), Some(spawn));
+        }
+    }
+
+    #[test]
+    fn test_encounters() {
+        use super::{ENCOUNTERS, SPECS};
+        use crate::Distribution;
+
+        let mut rng = calx::seeded_rng(&1);
+        for encounter in ENCOUNTERS.iter() {
+            let spawns: Vec<_> = encounter.sample(&mut rng);
+            assert!(!spawns.is_empty());
+            assert!(spawns.iter().all(|s| SPECS[s].is_mob()));
+        }
+    }
+
     #[test]
     fn test_entity_spawn_serialization() {
         use super::EntitySpawn;
@@ -1,6 +1,6 @@
 use crate::{
-    attack_damage, effect::Damage, msg, roll, Ability, ActionOutcome, AnimState, ItemType, Slot,
-    World,
+    attack_damage, effect::Damage, msg, roll, Ability, ActionOutcome, AnimState, Ending, ItemType,
+    Slot, World,
 };
 use calx::Dir6;
 use calx_ecs::Entity;
@@ -138,10 +138,15 @@ pub enum Status {
     ///
     /// This gets jumped up every time after the creature acted.
     Delayed,
+    /// Has used an innate ability and must recover before using one again.
+    Exhausted,
 }
 
 pub type Statuses = BTreeMap<Status, u32>;
 
+/// Abilities a creature can use at will without an item.
+pub type InnateAbilities = Vec<Ability>;
+
 /// Stats component in the ECS that supports caching applied modifiers for efficiency.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct StatsComponent {
@@ -211,7 +216,7 @@ impl World {
         self.ecs().stats.get(e).map(|s| s.base).unwrap_or_default()
     }
 
-    fn base_stats_mut(&mut self, e: Entity) -> Option<&mut Stats> {
+    pub(crate) fn base_stats_mut(&mut self, e: Entity) -> Option<&mut Stats> {
         self.ecs_mut().stats.get_mut(e).map(|s| &mut s.base)
     }
 
@@ -249,6 +254,10 @@ impl World {
             }
         }
 
+        if let Some(abilities) = self.ecs().innate_abilities.get(e) {
+            return abilities.clone();
+        }
+
         // Entity has no abilites.
         Vec::new()
     }
@@ -293,6 +302,26 @@ impl World {
                 self.gain_kill_xp(attacker, e);
             }
 
+            if Some(e) == self.player() {
+                let how = match damage_type {
+                    Damage::Physical => "Killed",
+                    Damage::Fire => "Burned to ash",
+                    Damage::Electricity => "Electrocuted",
+                };
+                let cause = match source {
+                    Some(attacker) if attacker != e => {
+                        format!("{} by {}", how, self.noun(attacker).a_name())
+                    }
+                    _ => how.to_string(),
+                };
+                self.flags.ending = Some(Ending {
+                    name: self.entity_name(e),
+                    victory: false,
+                    cause,
+                    stats: self.stats(e),
+                });
+            }
+
             if let Some(loc) = self.location(e) {
                 if self.player_sees(loc) {
                     // TODO: message templating
@@ -306,6 +335,10 @@ impl World {
                         self.subject(e)
                     );
                 }
+                self.drop_inventory(e, loc);
+                if damage_type != Damage::Fire {
+                    self.leave_corpse(e, loc);
+                }
                 self.spawn_fx(loc, AnimState::Gib);
             }
             self.kill_entity(e);
@@ -469,6 +502,8 @@ impl World {
         }
 
         if self.is_player(e) {
+            // Player picks a perk for each new level.
+            self.flags.pending_perks += change as u32;
             msg!("[One] feel[s] stronger."; self.subject(e));
         } else {
             msg!("[One] look[s] stronger."; self.subject(e));
@@ -14,7 +14,7 @@ impl World {
     pub fn terrain(&self, loc: Location) -> Terrain {
         let mut t = self.world_cache.get_terrain(loc);
 
-        if t == Terrain::Door && self.has_mobs(loc) {
+        if (t == Terrain::Door || t == Terrain::LockedDoor) && self.has_mobs(loc) {
             // Standing in the doorway opens the door.
             t = Terrain::OpenDoor;
         }
@@ -145,6 +145,7 @@ terrain_enum! {
     Rock:        TerrainData { name: "rock",      kind: Kind::Block,  form: Form::Blob,  map_chars: "*#",  is_regular: true,  color: 0xB84 },
     Door:        TerrainData { name: "door",      kind: Kind::Door,   form: Form::Wall,  map_chars: "|",   is_regular: true,  color: 0x842 },
     OpenDoor:    TerrainData { name: "open door", kind: Kind::Ground, form: Form::Wall,  map_chars: "",    is_regular: false, color: 0xFAF },
+    LockedDoor:  TerrainData { name: "locked door", kind: Kind::Block, form: Form::Wall, map_chars: "",    is_regular: false, color: 0xA62 },
     Window:      TerrainData { name: "window",    kind: Kind::Window, form: Form::Wall,  map_chars: "+",   is_regular: true,  color: 0xBFF },
     Pillar:      TerrainData { name: "pillar",    kind: Kind::Window, form: Form::Prop,  map_chars: "I",   is_regular: true,  color: 0xCCD },
     // TODO: Get rid of grass2, give render a coherent noise source for tiles and make it do the
@@ -0,0 +1,283 @@
+//! Multi-turn movement over known terrain
+
+use crate::{ActionOutcome, AnimState, FovStatus, ItemType, Location, Terrain, World};
+use calx::{Dir6, HexGeom};
+use calx_ecs::Entity;
+use std::collections::{HashMap, VecDeque};
+
+/// Upper limit for locations visited by a single path search.
+const MAX_SEARCH_NODES: usize = 8192;
+
+impl World {
+    /// Find the shortest path over known terrain to the nearest location that satisfies `is_goal`.
+    ///
+    /// The search is a breadth-first flood from the entity's location. It only goes
+    /// through locations in the entity's map memory and follows portals. Mobs in view block the
+    /// path except at the goal location.
+    ///
+    /// Return the direction and the destination of every step. The last step ends at the goal,
+    /// which can be a portal like a stairwell that leads into unexplored parts of the world.
+    pub fn known_path(
+        &self,
+        e: Entity,
+        is_goal: impl Fn(Location) -> bool,
+    ) -> Option<Vec<(Dir6, Location)>> {
+        let memory = self.ecs().map_memory.get(e)?;
+        let origin = self.location(e)?;
+
+        let mut come_from: HashMap<Location, (Dir6, Location)> = HashMap::new();
+        let mut incoming = VecDeque::new();
+        incoming.push_back(origin);
+
+        while let Some(loc) = incoming.pop_front() {
+            if loc != origin && memory.status(loc) == Some(FovStatus::Seen) && self.has_mobs(loc) {
+                continue;
+            }
+
+            if come_from.len() > MAX_SEARCH_NODES {
+                break;
+            }
+
+            for &dir in Dir6::iter() {
+                let entrance = loc + dir;
+                let next = loc.jump(self, dir);
+                if next == origin || come_from.contains_key(&next) {
+                    continue;
+                }
+                if !self.can_enter_terrain(e, next) {
+                    continue;
+                }
+
+                let is_known = memory.status(next).is_some();
+
+                if is_goal(next) && is_known {
+                    come_from.insert(next, (dir, loc));
+                    return Some(trace_path(&come_from, origin, next));
+                }
+                // Known portal into the unknown is fine as a final step.
+                if entrance != next && is_goal(entrance) && memory.status(entrance).is_some() {
+                    let mut path = trace_path(&come_from, origin, loc);
+                    path.push((dir, entrance));
+                    return Some(path);
+                }
+
+                if !is_known {
+                    continue;
+                }
+                come_from.insert(next, (dir, loc));
+                incoming.push_back(next);
+            }
+        }
+
+        None
+    }
+
+    /// Return the path the entity would travel along to reach destination.
+    pub fn travel_path(&self, e: Entity, destination: Location) -> Option<Vec<(Dir6, Location)>> {
+        if self.location(e)? == destination {
+            return None;
+        }
+        self.known_path(e, |loc| loc == destination)
+    }
+
+    /// Return the closest remembered location with the given terrain.
+    pub fn closest_known_terrain(&self, e: Entity, terrain: Terrain) -> Option<Location> {
+        let path = self.known_path(e, |loc| self.terrain(loc) == terrain)?;
+        path.last().map(|&(_, loc)| loc)
+    }
+
+    /// Return the direction to keep running in after a step in `dir`.
+    ///
+    /// Running follows corridors. It stops at junctions, in doorways, on items and when hostiles
+    /// come into view.
+    pub fn run_dir(&self, e: Entity, dir: Dir6) -> Option<Dir6> {
+        let loc = self.location(e)?;
+        if !self.visible_hostiles(e).is_empty()
+            || self.item_at(loc).is_some()
+            || self.terrain(loc).is_door()
+        {
+            return None;
+        }
+
+        let open_dirs = |loc: Location| -> Vec<Dir6> {
+            Dir6::iter()
+                .cloned()
+                .filter(|&d| self.can_enter_terrain(e, loc.jump(self, d)))
+                .collect()
+        };
+
+        let open = open_dirs(loc);
+        let back = dir + 3;
+        let next_dir = if open.len() <= 2 {
+            // In a corridor, go whichever way doesn't lead back.
+            *open.iter().find(|&&d| d != back)?
+        } else {
+            if open_dirs(loc.jump(self, back)).len() <= 2 {
+                // Came out of a corridor into a junction or a room.
+                return None;
+            }
+            if !open.contains(&dir) {
+                return None;
+            }
+            dir
+        };
+
+        if self.has_mobs(loc.jump(self, next_dir)) {
+            return None;
+        }
+        Some(next_dir)
+    }
+
+    /// Return hostile mobs the entity can currently see, closest first.
+    pub fn visible_hostiles(&self, e: Entity) -> Vec<Entity> {
+        let memory = match self.ecs().map_memory.get(e) {
+            Some(memory) => memory,
+            None => return Vec::new(),
+        };
+
+        let mut ret: Vec<(i32, Entity)> = self
+            .active_mobs()
+            .into_iter()
+            .filter(|&mob| mob != e && self.is_hostile_to(e, mob))
+            .filter_map(|mob| {
+                let offset = memory.chart.offset(self.location(mob)?)?;
+                Some((offset.hex_dist(), mob))
+            })
+            .collect();
+        ret.sort_by_key(|&(dist, _)| dist);
+        ret.into_iter().map(|(_, mob)| mob).collect()
+    }
+
+    /// Return an item at the location that exploration should pick up.
+    ///
+    /// Corpses and items the player has dropped are left alone, otherwise exploring would keep
+    /// going back to them.
+    fn explore_item_at(&self, loc: Location) -> Option<Entity> {
+        self.entities_at(loc).into_iter().find(|&item| {
+            matches!(self.ecs().item.get(item),
+                Some(i) if i.item_type != ItemType::Corpse && !i.discarded)
+        })
+    }
+
+    /// Return the item exploration should pick up at the entity's current location.
+    fn autoexplore_item(&self, e: Entity) -> Option<Entity> {
+        self.free_bag_slot(e)?;
+        self.explore_item_at(self.location(e)?)
+    }
+
+    /// Return the first step towards the nearest unexplored location or visible item.
+    pub fn autoexplore_dir(&self, e: Entity) -> Option<Dir6> {
+        let memory = self.ecs().map_memory.get(e)?;
+        let has_room = self.free_bag_slot(e).is_some();
+
+        let is_goal = |loc: Location| {
+            // Frontier of the explored area. A stairwell seen from above isn't an unexplored spot
+            // by itself.
+            let is_unknown = |dir: Dir6| {
+                memory.status(loc + dir).is_none() && memory.status(loc.jump(self, dir)).is_none()
+            };
+            if Dir6::iter().any(|&dir| is_unknown(dir)) {
+                return true;
+            }
+            has_room && memory.seen.contains(loc) && self.explore_item_at(loc).is_some()
+        };
+
+        self.known_path(e, is_goal)?.first().map(|&(dir, _)| dir)
+    }
+
+    /// Return whether exploring can do anything for the entity.
+    pub fn can_autoexplore(&self, e: Entity) -> bool {
+        !self.visible_hostiles(e).is_empty()
+            || self.autoexplore_item(e).is_some()
+            || self.autoexplore_dir(e).is_some()
+    }
+
+    /// Take one step of automatic exploration.
+    ///
+    /// Exploring is interrupted by visible hostiles. The closest one will show the blocking
+    /// animation and no time will pass.
+    pub(crate) fn autoexplore(&mut self, e: Entity) -> ActionOutcome {
+        if let Some(&mob) = self.visible_hostiles(e).first() {
+            let anim_tick = self.get_anim_tick();
+            if let Some(anim) = self.anim_mut(mob) {
+                anim.anim_start = anim_tick;
+                anim.state = AnimState::MobBlocks;
+            }
+            return None;
+        }
+
+        if let Some(item) = self.autoexplore_item(e) {
+            return self.entity_take(e, item);
+        }
+
+        let dir = self.autoexplore_dir(e)?;
+        self.entity_step(e, dir)
+    }
+}
+
+/// Follow search links from location back to origin and return the path from origin.
+fn trace_path(
+    come_from: &HashMap<Location, (Dir6, Location)>,
+    origin: Location,
+    mut loc: Location,
+) -> Vec<(Dir6, Location)> {
+    let mut path = Vec::new();
+    while loc != origin {
+        let (dir, prev) = come_from[&loc];
+        path.push((dir, loc));
+        loc = prev;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        effect::Damage, world::test::test_world, Command, ExternalEntity, ItemType, World,
+    };
+    use calx::Incremental;
+
+    /// Run the command once the player gets to act.
+    fn command(w: &mut World, cmd: Command) {
+        while !w.player_can_act() {
+            w.update(&Command::Wait);
+        }
+        w.update(&cmd);
+    }
+
+    #[test]
+    fn test_explore_skips_junk() {
+        let mut w = test_world();
+        let player = w.player().unwrap();
+        let loc = w.location(player).unwrap();
+        let scroll = ExternalEntity::from_name("scroll of lightning").unwrap();
+
+        let item = w.spawn(&scroll, loc);
+        assert_eq!(w.autoexplore_item(player), Some(item));
+
+        let slot = w.free_bag_slot(player).unwrap();
+        command(&mut w, Command::Take(item));
+        command(&mut w, Command::Drop(slot));
+        assert_eq!(w.location(item), Some(loc));
+        assert_eq!(w.autoexplore_item(player), None);
+
+        // Taking the item back makes it a regular item that stacks with fresh copies.
+        command(&mut w, Command::Take(item));
+        assert_eq!(w.entity_equipped(player, slot), Some(item));
+        let copy = w.spawn(&scroll, loc);
+        command(&mut w, Command::Take(copy));
+        assert_eq!(w.count(item), 2);
+
+        let dreg = w.spawn(&ExternalEntity::from_name("dreg").unwrap(), loc);
+        w.damage(dreg, 1000, Damage::Physical, None);
+        let corpse_loc = calx::hex_disc(loc, 8)
+            .find(|&l| {
+                w.entities_at(l)
+                    .iter()
+                    .any(|&e| w.item_type(e) == Some(ItemType::Corpse))
+            })
+            .unwrap();
+        assert_eq!(w.explore_item_at(corpse_loc), None);
+    }
+}
@@ -5,15 +5,50 @@ use std::sync::Arc;
 macro_rules! vaults {
     {$name:ident, $($content:expr,)+} => {
         lazy_static! {
-            pub static ref $name: Vec<Arc<Map>> = {
-                vec![
-                    $(Arc::new(Map::new_vault($content).unwrap()),)+
-                ]
-            };
+            pub static ref $name: Vec<Arc<Map>> = load_vaults(stringify!($name), &[$($content,)+]);
         }
     }
 }
 
+/// Parse a set of vaults and name them after the set and their index in it.
+fn load_vaults(set: &str, sources: &[&str]) -> Vec<Arc<Map>> {
+    sources
+        .iter()
+        .enumerate()
+        .map(|(i, src)| {
+            let name = format!("{}[{}]", set, i);
+            match Map::new_vault(src) {
+                Ok(map) => Arc::new(map.named(name)),
+                Err(e) => panic!("Invalid vault {}: {}", name, e),
+            }
+        })
+        .collect()
+}
+
+/// Return every vault in the game.
+///
+/// Panics with the name of the broken vault if any of the vault maps fail to parse.
+pub fn all_vaults() -> Vec<Arc<Map>> {
+    [
+        &*VAULTS,
+        &*BOSS_VAULTS,
+        &*ENTRANCES,
+        &*EXITS,
+        &*BUILDINGS,
+        &*WANG_HORIZ,
+        &*WANG_VERT,
+        &*GRASS_HORIZ,
+        &*GRASS_VERT,
+        &*FOREST_HORIZ,
+        &*FOREST_VERT,
+        &*DESERT_HORIZ,
+        &*DESERT_VERT,
+    ]
+    .iter()
+    .flat_map(|set| set.iter().cloned())
+    .collect()
+}
+
 vaults! {VAULTS,
     "
       ##++##
@@ -28,6 +63,39 @@ vaults! {VAULTS,
       #....#
       ##++##
     ",
+
+    "
+    ####+####
+    #.......#
+    #.I...I.#
+    +...E...+
+    #.I...I.#
+    #...A...#
+    ####+####
+    ",
+}
+
+vaults! {BOSS_VAULTS,
+    "
+    ####+####
+    #.I...I.#
+    #.......#
+    +...B...+
+    #.......#
+    #.I.a.I.#
+    ####+####
+    ",
+
+    "
+    #####+#####
+    #.........#
+    #.I#####I.#
+    #..#.B.#..#
+    #..#...#..#
+    #..##+##..#
+    #..a...a..#
+    #####+#####
+    ",
 }
 
 vaults! {ENTRANCES,
@@ -50,6 +118,46 @@ vaults! {EXITS,
     ",
 }
 
+vaults! {BUILDINGS,
+    "
+    ########
+    #......#
+    w......+
+    #......#
+    #.a....w
+    ########
+    ",
+    "
+    #####+####
+    #....#...#
+    w....+...w
+    #....#...#
+    #.a..#.a.#
+    #....#...#
+    ###+######
+    ",
+    "
+    ##+###
+    #....#
+    w....#
+    #....w
+    #..a.#
+    ######
+    ",
+    "
+    ####+#####
+    #........#
+    #.I....I.#
+    w........w
+    #...aa...#
+    #........#
+    w........w
+    #.I....I.#
+    #........#
+    #####+####
+    ",
+}
+
 vaults! {WANG_HORIZ,
     "
     ######.########.######
@@ -453,3 +561,12 @@ vaults! {DESERT_VERT,
     ___________
     ",
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_vaults() {
+        let vaults = super::all_vaults();
+        assert!(vaults.iter().all(|v| v.name().is_some()));
+    }
+}
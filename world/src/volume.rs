@@ -1,5 +1,5 @@
 use crate::{fov::SphereVolumeFov, Location, World};
-use calx::HexFov;
+use calx::{hex_cone, hex_ring, hex_supercover, hex_wedge, CellVector, Dir12, Dir6, HexFov};
 use std::iter::FromIterator;
 
 impl World {
@@ -24,4 +24,108 @@ impl Volume {
             HexFov::new(SphereVolumeFov::new(w, radius, origin)).map(|(pos, a)| a.origin + pos),
         ))
     }
+
+    /// Construct a volume from the cells of a sphere that are at the given offsets from origin.
+    ///
+    /// The shape spreads through portals and is stopped by walls the same way as a sphere.
+    fn shaped(w: &World, origin: Location, radius: u32, shape: &[CellVector]) -> Volume {
+        Volume(Vec::from_iter(
+            HexFov::new(SphereVolumeFov::new(w, radius, origin))
+                .filter(|(pos, _)| shape.contains(pos))
+                .map(|(pos, a)| a.origin + pos),
+        ))
+    }
+
+    /// Construct a line volume from origin along the offset vector, origin excluded.
+    ///
+    /// The line covers both cells when it passes between two. It follows portals and stops
+    /// before the first wall.
+    pub fn line(w: &World, origin: Location, end: CellVector) -> Volume {
+        let cells = hex_supercover(end);
+        let mut ret: Vec<Location> = Vec::new();
+
+        for i in 1..cells.len() {
+            // Supercover cells are each adjacent to the previous one.
+            let prev = if i == 1 { origin } else { ret[i - 2] };
+            let loc = prev.jump(w, Dir6::from_v2(cells[i] - cells[i - 1]));
+            if w.terrain(loc).blocks_shot() {
+                break;
+            }
+            ret.push(loc);
+        }
+        Volume(ret)
+    }
+
+    /// Construct the ring of cells at radius from origin that can be reached from origin.
+    pub fn ring(w: &World, origin: Location, radius: u32) -> Volume {
+        let shape: Vec<CellVector> = hex_ring(CellVector::zero(), radius as i32).collect();
+        Volume::shaped(w, origin, radius, &shape)
+    }
+
+    /// Construct a 60 degree cone of cells pointing from origin, origin excluded.
+    pub fn cone(w: &World, origin: Location, dir: impl Into<Dir12>, radius: u32) -> Volume {
+        Volume::shaped(w, origin, radius, &hex_cone(dir, radius as i32))
+    }
+
+    /// Construct a wedge of cells from origin with the given bearing and width, origin excluded.
+    ///
+    /// Angles are in radians, clockwise from north.
+    pub fn wedge(w: &World, origin: Location, angle: f32, width: f32, radius: u32) -> Volume {
+        Volume::shaped(w, origin, radius, &hex_wedge(angle, width, radius as i32))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Volume;
+    use crate::{world::test::test_world, Location, Sector, Terrain, World};
+    use calx::Dir6;
+
+    /// Find an open cell next to a cell that satisfies the predicate and the direction to it.
+    fn next_to(w: &World, pred: impl Fn(Location) -> bool) -> (Location, Dir6) {
+        Sector::new(0, 0, 0)
+            .iter()
+            .filter(|&loc| pred(loc))
+            .flat_map(|loc| Dir6::iter().map(move |&d| (loc - d.to_v2(), d)))
+            .find(|&(loc, _)| !w.terrain(loc).blocks_walk() && w.portal(loc).is_none())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_line_volume() {
+        let w = test_world();
+
+        // Line stops before a wall.
+        let (origin, dir) = next_to(&w, |loc| w.terrain(loc).blocks_shot());
+        assert!(Volume::line(&w, origin, dir.to_v2() * 4).0.is_empty());
+        let line = Volume::line(&w, origin, -dir.to_v2() * 4);
+        assert!(line.0.iter().all(|&loc| !w.terrain(loc).blocks_shot()));
+
+        // Line follows the stairs down and continues from the other end.
+        let (origin, dir) = next_to(&w, |loc| w.terrain(loc) == Terrain::Downstairs);
+        let line = Volume::line(&w, origin, dir.to_v2() * 2);
+        assert_eq!(line.0[0], w.portal(origin + dir.to_v2()).unwrap());
+        assert_eq!(line.0[0].z, origin.z - 1);
+    }
+
+    #[test]
+    fn test_shaped_volumes() {
+        let w = test_world();
+        let (origin, dir) = next_to(&w, |loc| w.terrain(loc).blocks_shot());
+        let wall = origin + dir.to_v2();
+        let sphere = Volume::sphere(&w, origin, 3).0;
+
+        for volume in &[
+            Volume::ring(&w, origin, 1),
+            Volume::cone(&w, origin, dir, 3),
+            Volume::wedge(&w, origin, dir.to_angle(), 2.0, 3),
+        ] {
+            // Shapes follow portals and stop at walls the same way as the sphere.
+            assert!(!volume.0.contains(&origin));
+            assert!(!volume.0.contains(&wall));
+            assert!(volume.0.iter().all(|loc| sphere.contains(loc)));
+        }
+        let ring = Volume::ring(&w, origin, 2).0;
+        assert!(ring.iter().all(|&loc| origin.distance_from(loc) == Some(2)));
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     ai, animations, components, desc, flags::Flags, item, spatial::Spatial, spec::EntitySpawn,
-    stats, world_cache::WorldCache, Distribution, ExternalEntity, Location, Rng, WorldSkeleton,
+    stats, world_cache::WorldCache, ExternalEntity, Location, Rng, WorldSkeleton,
 };
 use calx::seeded_rng;
 use serde_derive::{Deserialize, Serialize};
@@ -13,8 +13,10 @@ calx_ecs::build_ecs! {
     brain: ai::Brain,
     desc: desc::Desc,
     health: stats::Health,
+    innate_abilities: stats::InnateAbilities,
     item: item::Item,
     map_memory: components::MapMemory,
+    remains: item::Remains,
     stacking: item::Stacking,
     stats: stats::StatsComponent,
     status: stats::Statuses,
@@ -39,6 +41,10 @@ pub struct World {
     pub(crate) world_cache: WorldCache,
     /// Spawns from worldgen that have been generated in world.
     generated_spawns: HashSet<(Location, EntitySpawn)>,
+    /// Unique monsters that have been generated in world, each can only be generated once.
+    generated_uniques: HashSet<EntitySpawn>,
+    /// Unique monsters the player has seen and been told about.
+    pub(crate) met_uniques: HashSet<EntitySpawn>,
     /// Spatial index for game entities.
     pub(crate) spatial: Spatial,
     /// Global gamestate flags.
@@ -54,6 +60,8 @@ impl World {
             ecs: Default::default(),
             world_cache: WorldCache::new(world_seed.rng_seed, world_seed.world_skeleton.clone()),
             generated_spawns: Default::default(),
+            generated_uniques: Default::default(),
+            met_uniques: Default::default(),
             spatial: Default::default(),
             flags: Default::default(),
             rng: seeded_rng(&world_seed.rng_seed),
@@ -74,11 +82,34 @@ impl World {
         let seed = self.rng_seed();
 
         for (loc, s) in &spawns {
+            self.generated_spawns.insert((*loc, s.clone()));
+            // Another sector's boss vault may have already gotten this one.
+            if s.is_unique() && !self.generated_uniques.insert(s.clone()) {
+                continue;
+            }
+
             // Create one-off RNG from just the spawn info, will always run the same for same info.
             let mut rng = calx::seeded_rng(&(seed, loc, s));
+            let depth = self.world_cache.depth(*loc);
             // Construct loadout from the spawn info and generate it in world.
-            self.spawn(&s.sample(&mut rng), *loc);
-            self.generated_spawns.insert((*loc, s.clone()));
+            self.spawn(&s.generate(&mut rng, depth), *loc);
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{World, WorldSeed};
+    use crate::{ExternalEntity, WorldSkeleton};
+
+    /// Seed for a player in the tiny builtin world for testing game logic.
+    pub(crate) fn test_seed() -> WorldSeed {
+        WorldSeed {
+            rng_seed: 1,
+            world_skeleton: WorldSkeleton::builtin("tiny").unwrap(),
+            player_character: ExternalEntity::from_name("player").unwrap(),
+        }
+    }
+
+    pub(crate) fn test_world() -> World { World::new(&test_seed()) }
+}
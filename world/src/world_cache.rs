@@ -1,7 +1,7 @@
 use crate::{
     location::{Location, Portal},
     map::MapCell,
-    sector::{self, Sector, WorldSkeleton},
+    sector::{self, ConnectedSectorSpec, Connection, Sector, SectorDir, WorldSkeleton},
     spec::EntitySpawn,
     terrain::Terrain,
 };
@@ -37,6 +37,13 @@ impl WorldCache {
 
     pub fn seed(&self) -> u32 { self.seed }
 
+    /// Return the depth of the sector the location is in.
+    pub fn depth(&self, loc: Location) -> i32 {
+        self.skeleton
+            .get(&Sector::from(loc))
+            .map_or(0, |spec| spec.depth)
+    }
+
     /// Get the location where the player enters the world.
     pub fn player_entrance(&self) -> Location {
         // Player start in sector 0. Expect generation logic to set player position when
@@ -67,6 +74,14 @@ impl WorldCache {
 
     pub fn sector_exists(&self, sector: Sector) -> bool { self.skeleton.contains_key(&sector) }
 
+    /// Return how a sector connects to its neighbor in the given direction.
+    pub fn connection(&self, sector: Sector, dir: SectorDir) -> Connection {
+        if !self.sector_exists(sector) {
+            return Connection::Blocked;
+        }
+        ConnectedSectorSpec::new(self.seed, sector, &self.skeleton).connection(dir)
+    }
+
     /// Return latest list of spawns.
     ///
     /// `WorldCache` will return spawns from regions that have been loaded into cache. Caller will